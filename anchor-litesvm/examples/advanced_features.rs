@@ -8,7 +8,7 @@
 /// - Transaction metadata analysis
 
 use anchor_litesvm::{
-    AnchorLiteSVM, TestHelpers, AssertionHelpers, tuple_args,
+    AnchorLiteSVM, TestHelpers, AssertionHelpers, TimeHelpers, tuple_args,
 };
 use solana_sdk::signature::{Keypair, Signer};
 use solana_program::pubkey::Pubkey;
@@ -430,9 +430,9 @@ fn example_time_based_testing() {
     let mut ctx = AnchorLiteSVM::build_with_program(program_id, &program_bytes);
     let user = ctx.create_funded_account(10_000_000_000).unwrap();
 
-    // Create a time-locked vault
+    // Create a time-locked vault that unlocks in one day
     let vault = Pubkey::new_unique();
-    let unlock_time = 1_700_000_000i64; // Some future timestamp
+    let unlock_time = ctx.clock().unix_timestamp + 24 * 60 * 60;
 
     ctx.instruction_builder("create_timelock")
         .signer("user", &user)
@@ -455,18 +455,20 @@ fn example_time_based_testing() {
     // Should fail
     assert!(early_withdraw.is_err(), "Withdrawal should fail before unlock time");
 
-    // Note: In a real test, you would need to manipulate the clock
-    // or wait for the appropriate time. LiteSVM may have utilities for this.
+    // Move the clock past the unlock time (slot, epoch and timestamp stay consistent)
+    ctx.advance_time(std::time::Duration::from_secs(24 * 60 * 60 + 1));
 
-    // After time has passed (simulated)
-    // This would normally require clock manipulation
-    let _late_withdraw = ctx.instruction_builder("withdraw_timelock")
+    let late_withdraw = ctx.instruction_builder("withdraw_timelock")
         .signer("user", &user)
         .account_mut("vault", vault)
         .account_mut("user", user.pubkey())
         .args(tuple_args(()))
         .execute(&mut ctx, &[&user]);
 
-    // In a real test with proper time manipulation, this would succeed
-    // For this example, it demonstrates the pattern
-}
\ No newline at end of file
+    late_withdraw.unwrap().assert_success();
+
+    // Other ways to move time:
+    // ctx.set_unix_timestamp(unlock_time);
+    // ctx.warp_to_slot(1_000_000);
+    // ctx.advance_epoch();
+}
@@ -0,0 +1,32 @@
+//! Example serving an Anchor program over JSON-RPC from LiteSVM
+//!
+//! Starts a local Solana JSON-RPC endpoint backed by LiteSVM, so TypeScript
+//! Anchor tests can run without `solana-test-validator`:
+//!
+//! ```text
+//! cargo run --example rpc_server -- <PROGRAM_ID> <PATH_TO_SO> [ADDRESS]
+//! ANCHOR_PROVIDER_URL=http://127.0.0.1:8899 yarn run ts-mocha -p ./tsconfig.json tests/**/*.ts
+//! ```
+
+use anchor_litesvm::{AnchorLiteSVM, RpcServer};
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (Some(program_id), Some(program_path)) = (args.next(), args.next()) else {
+        eprintln!("Usage: rpc_server <PROGRAM_ID> <PATH_TO_SO> [ADDRESS]");
+        std::process::exit(1);
+    };
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:8899".to_string());
+
+    let program_id = Pubkey::from_str(&program_id).expect("invalid program ID");
+    let program_bytes = std::fs::read(&program_path).expect("failed to read program");
+    let ctx = AnchorLiteSVM::build_with_program(program_id, &program_bytes);
+
+    let server = RpcServer::new(ctx)
+        .spawn(address)
+        .expect("failed to start server");
+    println!("Serving {} at {}", program_id, server.url());
+    server.join();
+}
@@ -1,4 +1,4 @@
-use anchor_lang::AccountDeserialize;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
 use litesvm::LiteSVM;
 use solana_program::pubkey::Pubkey;
 use thiserror::Error;
@@ -11,30 +11,43 @@ pub enum AccountError {
     #[error("Failed to deserialize account: {0}")]
     DeserializationError(String),
 
-    #[error("Account discriminator mismatch")]
-    DiscriminatorMismatch,
+    #[error("Account discriminator mismatch: expected {expected:?}, got {actual:?}")]
+    DiscriminatorMismatch {
+        /// The discriminator of the requested account type
+        expected: Vec<u8>,
+        /// The leading bytes of the account data
+        actual: Vec<u8>,
+    },
 }
 
 /// Fetches and deserializes an Anchor account from LiteSVM
 ///
 /// This function:
 /// 1. Retrieves the account data from LiteSVM
-/// 2. Deserializes it using Anchor's AccountDeserialize trait
-/// 3. Handles the 8-byte discriminator that Anchor prepends to account data
+/// 2. Checks the account's discriminator (of any length, including custom
+///    `#[account(discriminator = ...)]` values) against `T::DISCRIMINATOR`
+/// 3. Deserializes it using Anchor's AccountDeserialize trait
 pub fn get_anchor_account<T>(
     svm: &LiteSVM,
     address: &Pubkey,
 ) -> Result<T, AccountError>
 where
-    T: AccountDeserialize,
+    T: AccountDeserialize + Discriminator,
 {
     // Get the account from LiteSVM
     let account = svm
         .get_account(address)
         .ok_or_else(|| AccountError::AccountNotFound(*address))?;
 
+    if !account.data.starts_with(T::DISCRIMINATOR) {
+        let len = T::DISCRIMINATOR.len().min(account.data.len());
+        return Err(AccountError::DiscriminatorMismatch {
+            expected: T::DISCRIMINATOR.to_vec(),
+            actual: account.data[..len].to_vec(),
+        });
+    }
+
     // Deserialize using Anchor's method
-    // Note: Anchor accounts have an 8-byte discriminator at the beginning
     let mut data_slice: &[u8] = &account.data;
     T::try_deserialize(&mut data_slice)
         .map_err(|e| AccountError::DeserializationError(e.to_string()))
@@ -43,7 +56,9 @@ where
 /// Fetches and deserializes an Anchor account without discriminator check
 ///
 /// Use this for accounts that don't have the standard Anchor discriminator
-/// (e.g., some PDAs or custom account layouts)
+/// (e.g., some PDAs or custom account layouts). The first 8 bytes are always
+/// skipped; for custom discriminator lengths use
+/// [`get_anchor_account_skip_discriminator`].
 pub fn get_anchor_account_unchecked<T>(
     svm: &LiteSVM,
     address: &Pubkey,
@@ -67,9 +82,31 @@ where
         .map_err(|e| AccountError::DeserializationError(e.to_string()))
 }
 
+/// Fetches and deserializes an Anchor account, skipping `T`'s discriminator without checking it
+///
+/// Skips `T::DISCRIMINATOR.len()` bytes, so custom discriminators such as
+/// `#[account(discriminator = 1)]` are handled.
+pub fn get_anchor_account_skip_discriminator<T>(
+    svm: &LiteSVM,
+    address: &Pubkey,
+) -> Result<T, AccountError>
+where
+    T: AnchorDeserialize + Discriminator,
+{
+    let account = svm
+        .get_account(address)
+        .ok_or(AccountError::AccountNotFound(*address))?;
+
+    let data = account.data.get(T::DISCRIMINATOR.len()..).ok_or_else(|| {
+        AccountError::DeserializationError("Account data too small for Anchor account".to_string())
+    })?;
+    T::try_from_slice(data).map_err(|e| AccountError::DeserializationError(e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anchor_lang::prelude::borsh;
 
     #[test]
     fn test_account_error_display() {
@@ -79,7 +116,84 @@ mod tests {
         let error = AccountError::DeserializationError("test error".to_string());
         assert!(error.to_string().contains("Failed to deserialize"));
 
-        let error = AccountError::DiscriminatorMismatch;
-        assert_eq!(error.to_string(), "Account discriminator mismatch");
+        let error = AccountError::DiscriminatorMismatch {
+            expected: vec![1],
+            actual: vec![2],
+        };
+        assert_eq!(
+            error.to_string(),
+            "Account discriminator mismatch: expected [1], got [2]"
+        );
+    }
+
+    #[derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize)]
+    struct Counter {
+        count: u64,
+    }
+
+    impl Discriminator for Counter {
+        const DISCRIMINATOR: &'static [u8] = &[1];
+    }
+
+    impl AccountDeserialize for Counter {
+        fn try_deserialize_unchecked(buf: &mut &[u8]) -> anchor_lang::Result<Self> {
+            let mut data = &buf[Self::DISCRIMINATOR.len()..];
+            anchor_lang::AnchorDeserialize::deserialize(&mut data)
+                .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into())
+        }
+    }
+
+    fn set_account(svm: &mut LiteSVM, data: Vec<u8>) -> Pubkey {
+        let address = Pubkey::new_unique();
+        svm.set_account(
+            address,
+            solana_sdk::account::Account {
+                lamports: 1_000_000,
+                data,
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+        address
+    }
+
+    #[test]
+    fn test_custom_discriminator() {
+        let mut svm = LiteSVM::new();
+        let address = set_account(&mut svm, vec![1, 42, 0, 0, 0, 0, 0, 0, 0]);
+
+        let counter: Counter = get_anchor_account(&svm, &address).unwrap();
+        assert_eq!(counter.count, 42);
+    }
+
+    #[test]
+    fn test_skip_discriminator() {
+        let mut svm = LiteSVM::new();
+        // Only the 1-byte discriminator is skipped, and it is not checked
+        let address = set_account(&mut svm, vec![7, 42, 0, 0, 0, 0, 0, 0, 0]);
+        let counter: Counter = get_anchor_account_skip_discriminator(&svm, &address).unwrap();
+        assert_eq!(counter.count, 42);
+
+        let address = set_account(&mut svm, vec![]);
+        assert!(matches!(
+            get_anchor_account_skip_discriminator::<Counter>(&svm, &address),
+            Err(AccountError::DeserializationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_discriminator_mismatch() {
+        let mut svm = LiteSVM::new();
+        let address = set_account(&mut svm, vec![7, 42, 0, 0, 0, 0, 0, 0, 0]);
+
+        match get_anchor_account::<Counter>(&svm, &address) {
+            Err(AccountError::DiscriminatorMismatch { expected, actual }) => {
+                assert_eq!(expected, vec![1]);
+                assert_eq!(actual, vec![7]);
+            }
+            other => panic!("expected DiscriminatorMismatch, got {:?}", other.err()),
+        }
     }
 }
\ No newline at end of file
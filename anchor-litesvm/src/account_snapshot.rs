@@ -0,0 +1,437 @@
+//! Golden-file snapshots of account state
+//!
+//! An [`AccountSnapshot`] renders an account to stable text and compares it
+//! with `snapshots/<name>.snap` next to the test crate's `Cargo.toml`. The
+//! file is meant to be checked in; a missing one fails like a mismatch.
+//!
+//! Account data is rendered from the owning program's IDL when one is loaded,
+//! from SPL Token / Token-2022 state for mints and token accounts, or as a hex
+//! dump otherwise. [`AccountSnapshot::assert_anchor`] renders a typed Anchor
+//! account with `Debug` instead.
+//!
+//! On mismatch the new rendering is written to `<name>.snap.new` for review
+//! and the assertion fails with a diff. Accept it by renaming the file, or
+//! re-run with `UPDATE_ACCOUNT_SNAPSHOTS=1` to write every missing or
+//! mismatching snapshot.
+
+use crate::idl::{decode_account, hex};
+use crate::test_helpers::token::{is_token_program, unpack_mint, unpack_token_account};
+use crate::AnchorContext;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use serde_json::json;
+use solana_program::program_option::COption;
+use solana_program::pubkey::Pubkey;
+use std::fmt::{Debug, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Environment variable that makes mismatching snapshots get rewritten
+pub const UPDATE_ENV_VAR: &str = "UPDATE_ACCOUNT_SNAPSHOTS";
+
+/// Default snapshot directory, relative to the crate running the tests
+pub const DEFAULT_DIR_NAME: &str = "snapshots";
+
+#[derive(Error, Debug)]
+pub enum AccountSnapshotError {
+    #[error("Failed to access account snapshot {path}: {source}")]
+    Io {
+        /// The snapshot file
+        path: PathBuf,
+        /// The underlying IO error
+        source: std::io::Error,
+    },
+
+    #[error(
+        "Account snapshot `{name}` is missing: {path}. \
+         Review {path}.new and rename it to accept, or re-run with UPDATE_ACCOUNT_SNAPSHOTS=1."
+    )]
+    Missing {
+        /// The snapshot name
+        name: String,
+        /// The snapshot file
+        path: PathBuf,
+    },
+
+    #[error(
+        "Account snapshot `{name}` does not match {path}:\n{diff}\
+         Review {path}.new and rename it to accept, or re-run with UPDATE_ACCOUNT_SNAPSHOTS=1."
+    )]
+    Mismatch {
+        /// The snapshot name
+        name: String,
+        /// The snapshot file
+        path: PathBuf,
+        /// Line diff from the stored to the new snapshot
+        diff: String,
+    },
+}
+
+/// A named golden file for account state
+///
+/// # Example
+///
+/// ```ignore
+/// // Uses snapshots/escrow_after_make.snap next to the test crate's Cargo.toml
+/// ctx.assert_account_snapshot("escrow_after_make", &escrow_pda);
+///
+/// // Replace values that change between runs with placeholders
+/// AccountSnapshot::new("escrow_after_make")
+///     .redact(&maker.pubkey(), "maker")
+///     .redact_field("bump")
+///     .assert(&ctx, &escrow_pda);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AccountSnapshot {
+    name: String,
+    dir: PathBuf,
+    redactions: Vec<(String, String)>,
+    redacted_fields: Vec<String>,
+    update: Option<bool>,
+}
+
+impl AccountSnapshot {
+    /// Snapshot `name` in the default directory
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        Self {
+            name: name.to_string(),
+            dir: dir.join(DEFAULT_DIR_NAME),
+            redactions: Vec::new(),
+            redacted_fields: Vec::new(),
+            update: None,
+        }
+    }
+
+    /// Store the snapshot file in `dir` instead
+    pub fn snapshot_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Render `pubkey` as `[label]`, for addresses that differ between runs
+    pub fn redact(mut self, pubkey: &Pubkey, label: &str) -> Self {
+        self.redactions.push((pubkey.to_string(), format!("[{}]", label)));
+        self
+    }
+
+    /// Render the value of every field called `field` as `[field]`
+    ///
+    /// Meant for scalar fields such as PDA bumps that depend on random keys.
+    pub fn redact_field(mut self, field: &str) -> Self {
+        self.redacted_fields.push(field.to_string());
+        self
+    }
+
+    /// Write the snapshot instead of comparing, regardless of `UPDATE_ACCOUNT_SNAPSHOTS`
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = Some(update);
+        self
+    }
+
+    /// The snapshot file
+    pub fn path(&self) -> PathBuf {
+        let file_name: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.snap", file_name))
+    }
+
+    /// Assert that the account at `address` matches the snapshot
+    pub fn assert(&self, ctx: &AnchorContext, address: &Pubkey) {
+        self.assert_text(&render_account(ctx, address));
+    }
+
+    /// Assert that the typed Anchor account at `address` matches the snapshot
+    ///
+    /// The data is rendered with `{:#?}`, for accounts whose program has no IDL loaded.
+    pub fn assert_anchor<T>(&self, ctx: &AnchorContext, address: &Pubkey)
+    where
+        T: AccountDeserialize + Discriminator + Debug,
+    {
+        let account: T = ctx
+            .get_anchor_account(address)
+            .unwrap_or_else(|e| panic!("Failed to load account {}: {}", address, e));
+        let mut text = render_header(ctx, address);
+        let type_name = std::any::type_name::<T>().rsplit("::").next().unwrap_or_default();
+        writeln!(text, "type: {}", type_name).unwrap();
+        writeln!(text, "data: {:#?}", account).unwrap();
+        self.assert_text(&text);
+    }
+
+    /// Like [`check_text`](Self::check_text), but panics on mismatch
+    pub fn assert_text(&self, text: &str) {
+        if let Err(e) = self.check_text(text) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Compare already rendered text against the snapshot
+    ///
+    /// Redactions are applied first. Missing and mismatching snapshots are
+    /// errors; when updating (see [`update`](Self::update),
+    /// `UPDATE_ACCOUNT_SNAPSHOTS` by default) they are written instead.
+    pub fn check_text(&self, text: &str) -> Result<(), AccountSnapshotError> {
+        let update = self.update.unwrap_or_else(|| {
+            std::env::var_os(UPDATE_ENV_VAR).is_some_and(|value| value != "0")
+        });
+        self.check_inner(text, update)
+    }
+
+    fn check_inner(&self, text: &str, update: bool) -> Result<(), AccountSnapshotError> {
+        let actual = self.redacted(text);
+        let path = self.path();
+        let pending = path.with_extension("snap.new");
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| AccountSnapshotError::Io { path, source }
+        };
+
+        let expected = match std::fs::read_to_string(&path) {
+            Ok(expected) => Some(expected),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(source) => return Err(AccountSnapshotError::Io { path, source }),
+        };
+
+        match expected {
+            Some(expected) if expected == actual => {}
+            Some(expected) if !update => {
+                std::fs::write(&pending, &actual).map_err(io_error(&pending))?;
+                return Err(AccountSnapshotError::Mismatch {
+                    name: self.name.clone(),
+                    diff: diff_lines(&expected, &actual),
+                    path,
+                });
+            }
+            None if !update => {
+                std::fs::create_dir_all(&self.dir).map_err(io_error(&self.dir))?;
+                std::fs::write(&pending, &actual).map_err(io_error(&pending))?;
+                return Err(AccountSnapshotError::Missing {
+                    name: self.name.clone(),
+                    path,
+                });
+            }
+            _ => {
+                std::fs::create_dir_all(&self.dir).map_err(io_error(&self.dir))?;
+                std::fs::write(&path, &actual).map_err(io_error(&path))?;
+            }
+        }
+
+        match std::fs::remove_file(&pending) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(&pending)(e)),
+            _ => Ok(()),
+        }
+    }
+
+    fn redacted(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (pubkey, label) in &self.redactions {
+            text = text.replace(pubkey, label);
+        }
+
+        let mut out = String::new();
+        for line in text.lines() {
+            let line = self
+                .redacted_fields
+                .iter()
+                .find_map(|field| redact_field_line(line, field))
+                .unwrap_or_else(|| line.to_string());
+            out.push_str(&line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Render an account as snapshot text
+///
+/// Closed or missing accounts render as `exists: false`.
+pub fn render_account(ctx: &AnchorContext, address: &Pubkey) -> String {
+    let mut text = render_header(ctx, address);
+    let Some(account) = ctx.svm.get_account(address) else {
+        return text;
+    };
+    if account.lamports == 0 && account.data.is_empty() {
+        return text;
+    }
+
+    let decoded = ctx
+        .idl_for(&account.owner)
+        .and_then(|idl| decode_account(idl, &account.data))
+        .map(|(name, value)| (name.to_string(), value))
+        .or_else(|| decode_token(&account.owner, &account.data));
+
+    match decoded {
+        Some((type_name, value)) => {
+            writeln!(text, "type: {}", type_name).unwrap();
+            writeln!(text, "data: {}", serde_json::to_string_pretty(&value).unwrap()).unwrap();
+        }
+        None if account.data.is_empty() => {}
+        None => {
+            writeln!(text, "data:").unwrap();
+            for chunk in account.data.chunks(32) {
+                writeln!(text, "  {}", hex(chunk)).unwrap();
+            }
+        }
+    }
+    text
+}
+
+fn render_header(ctx: &AnchorContext, address: &Pubkey) -> String {
+    let mut text = String::new();
+    match ctx.svm.get_account(address) {
+        Some(account) if account.lamports > 0 || !account.data.is_empty() => {
+            writeln!(text, "owner: {}", account.owner).unwrap();
+            writeln!(text, "lamports: {}", account.lamports).unwrap();
+            writeln!(text, "executable: {}", account.executable).unwrap();
+            writeln!(text, "data_len: {}", account.data.len()).unwrap();
+        }
+        _ => writeln!(text, "exists: false").unwrap(),
+    }
+    text
+}
+
+fn decode_token(owner: &Pubkey, data: &[u8]) -> Option<(String, serde_json::Value)> {
+    fn optional(key: COption<Pubkey>) -> serde_json::Value {
+        Option::<Pubkey>::from(key).map(|key| key.to_string()).into()
+    }
+
+    if !is_token_program(owner) {
+        return None;
+    }
+
+    if let Ok(token) = unpack_token_account(data) {
+        return Some((
+            "TokenAccount".to_string(),
+            json!({
+                "mint": token.mint.to_string(),
+                "owner": token.owner.to_string(),
+                "amount": token.amount,
+                "delegate": optional(token.delegate),
+                "state": format!("{:?}", token.state),
+                "is_native": Option::<u64>::from(token.is_native),
+                "delegated_amount": token.delegated_amount,
+                "close_authority": optional(token.close_authority),
+            }),
+        ));
+    }
+
+    let mint = unpack_mint(data).ok()?;
+    Some((
+        "Mint".to_string(),
+        json!({
+            "mint_authority": optional(mint.mint_authority),
+            "supply": mint.supply,
+            "decimals": mint.decimals,
+            "is_initialized": mint.is_initialized,
+            "freeze_authority": optional(mint.freeze_authority),
+        }),
+    ))
+}
+
+/// Replace the value of `field` on a `"field": value` or `field: value` line
+fn redact_field_line(line: &str, field: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let rest = trimmed
+        .strip_prefix(&format!("\"{}\":", field))
+        .or_else(|| trimmed.strip_prefix(&format!("{}:", field)))?;
+    let key = &trimmed[..trimmed.len() - rest.len()];
+    let comma = if rest.trim_end().ends_with(',') { "," } else { "" };
+    Some(format!("{}{} [{}]{}", indent, key, field, comma))
+}
+
+/// Line diff from `old` to `new`, with `-`/`+` markers
+fn diff_lines(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence lengths of every pair of suffixes
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut out = String::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            writeln!(out, "  {}", old[i]).unwrap();
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            writeln!(out, "- {}", old[i]).unwrap();
+            i += 1;
+        } else {
+            writeln!(out, "+ {}", new[j]).unwrap();
+            j += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_snapshot(name: &str) -> AccountSnapshot {
+        let dir = std::env::temp_dir().join(format!("anchor_litesvm_snapshots_{}", std::process::id()));
+        let snapshot = AccountSnapshot::new(name).snapshot_dir(dir);
+        let _ = std::fs::remove_file(snapshot.path());
+        snapshot
+    }
+
+    #[test]
+    fn test_redactions() {
+        let maker = Pubkey::new_unique();
+        let snapshot = AccountSnapshot::new("escrow")
+            .redact(&maker, "maker")
+            .redact_field("bump");
+
+        let text = format!("data: {{\n  \"bump\": 254,\n  \"maker\": \"{}\"\n}}\nbump: 3\n", maker);
+        assert_eq!(
+            snapshot.redacted(&text),
+            "data: {\n  \"bump\": [bump],\n  \"maker\": \"[maker]\"\n}\nbump: [bump]\n"
+        );
+    }
+
+    #[test]
+    fn test_write_compare_and_update() {
+        let snapshot = temp_snapshot("escrow::after_make");
+        assert!(snapshot.path().ends_with("escrow__after_make.snap"));
+        let pending = snapshot.path().with_extension("snap.new");
+
+        // A missing snapshot fails and leaves the rendering for review
+        let err = snapshot.check_inner("seed: 42\namount: 10\n", false).unwrap_err();
+        assert!(matches!(err, AccountSnapshotError::Missing { .. }));
+        assert!(!snapshot.path().exists());
+        assert_eq!(std::fs::read_to_string(&pending).unwrap(), "seed: 42\namount: 10\n");
+
+        snapshot.check_inner("seed: 42\namount: 10\n", true).unwrap();
+        assert!(!pending.exists());
+        snapshot.check_inner("seed: 42\namount: 10\n", false).unwrap();
+
+        let err = snapshot.check_inner("seed: 42\namount: 11\n", false).unwrap_err();
+        let AccountSnapshotError::Mismatch { diff, .. } = &err else {
+            panic!("expected Mismatch, got {:?}", err);
+        };
+        assert_eq!(diff, "  seed: 42\n- amount: 10\n+ amount: 11\n");
+        assert_eq!(std::fs::read_to_string(&pending).unwrap(), "seed: 42\namount: 11\n");
+        assert!(err.to_string().contains("UPDATE_ACCOUNT_SNAPSHOTS=1"));
+
+        // Updating accepts the new rendering and clears the pending file
+        snapshot.check_inner("seed: 42\namount: 11\n", true).unwrap();
+        assert_eq!(std::fs::read_to_string(snapshot.path()).unwrap(), "seed: 42\namount: 11\n");
+        assert!(!pending.exists());
+        std::fs::remove_file(snapshot.path()).unwrap();
+    }
+}
@@ -0,0 +1,311 @@
+//! Anchor error decoding and assertions
+//!
+//! This module maps a failed transaction back to the Anchor error that caused
+//! it, using the `InstructionError::Custom(n)` code and the
+//! `AnchorError ... Error Code: ... Error Number: ... Error Message: ...` log
+//! line that Anchor programs emit.
+
+use crate::transaction::{TransactionError, TransactionResult};
+use anchor_lang::error::{Error as AnchorLangError, ErrorCode};
+use solana_program::program_error::ProgramError;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError as SdkTransactionError;
+use std::fmt;
+
+/// An Anchor error decoded from a failed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorErrorInfo {
+    /// The error number (e.g. 6000 for the first `#[error_code]` variant)
+    pub code: u32,
+    /// The error name (e.g. `InvalidAmount`), if it was logged
+    pub name: Option<String>,
+    /// The `#[msg(...)]` text, if it was logged
+    pub message: Option<String>,
+    /// The account or source location that raised the error, if logged
+    pub origin: Option<String>,
+}
+
+impl AnchorErrorInfo {
+    /// Decode an Anchor error from a runtime error and transaction logs
+    ///
+    /// The code comes from `InstructionError::Custom`, and name, message and
+    /// origin from the last `AnchorError` log line. Returns `None` if the
+    /// failure was not a custom program error.
+    pub fn decode(error: &SdkTransactionError, logs: &[String]) -> Option<Self> {
+        let custom_code = match error {
+            SdkTransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(*code),
+            _ => None,
+        };
+
+        let logged = logs.iter().rev().find_map(|log| parse_anchor_error_log(log));
+
+        match (custom_code, logged) {
+            (Some(code), Some(info)) if info.code == code => Some(info),
+            (Some(code), _) => Some(Self {
+                code,
+                name: None,
+                message: None,
+                origin: None,
+            }),
+            (None, logged) => logged,
+        }
+    }
+
+    /// Whether this is one of Anchor's account constraint errors (2000-2999)
+    pub fn is_constraint_error(&self) -> bool {
+        (2000..3000).contains(&self.code)
+    }
+}
+
+impl fmt::Display for AnchorErrorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} ({})", name, self.code)?,
+            None => write!(f, "{}", self.code)?,
+        }
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        if let Some(origin) = &self.origin {
+            write!(f, " [{}]", origin)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a single `AnchorError ...` log line
+fn parse_anchor_error_log(log: &str) -> Option<AnchorErrorInfo> {
+    let log = log.strip_prefix("Program log: ").unwrap_or(log);
+    let rest = log.strip_prefix("AnchorError ")?;
+
+    let (origin_part, rest) = rest.split_once(". Error Code: ")?;
+    let (name, rest) = rest.split_once(". Error Number: ")?;
+    let (code, message) = rest.split_once(". Error Message: ")?;
+
+    let origin = origin_part
+        .strip_prefix("caused by account: ")
+        .or_else(|| origin_part.strip_prefix("thrown in "))
+        .map(str::to_string);
+
+    Some(AnchorErrorInfo {
+        code: code.trim().parse().ok()?,
+        name: Some(name.to_string()),
+        message: Some(message.strip_suffix('.').unwrap_or(message).to_string()),
+        origin,
+    })
+}
+
+/// Get the code, name and message of an `#[error_code]` variant
+fn expected_error<E: Into<AnchorLangError>>(expected: E) -> AnchorErrorInfo {
+    match expected.into() {
+        AnchorLangError::AnchorError(err) => AnchorErrorInfo {
+            code: err.error_code_number,
+            name: Some(err.error_name),
+            message: Some(err.error_msg),
+            origin: None,
+        },
+        AnchorLangError::ProgramError(err) => match err.program_error {
+            ProgramError::Custom(code) => AnchorErrorInfo {
+                code,
+                name: None,
+                message: None,
+                origin: None,
+            },
+            other => panic!("{:?} is not a custom program error", other),
+        },
+    }
+}
+
+/// Assertions on the Anchor error a transaction failed with
+///
+/// Implemented for [`TransactionError`] and for the `Result` returned by
+/// `execute` / `send_instruction`, so the result can be checked directly.
+///
+/// # Example
+///
+/// ```ignore
+/// use anchor_escrow::errors::EscrowError;
+/// use anchor_lang::error::ErrorCode;
+/// use anchor_litesvm::AnchorErrorAssertions;
+///
+/// ctx.instruction_builder("make")
+///     // ...
+///     .execute(&mut ctx, &[&maker])
+///     .assert_anchor_error(EscrowError::InvalidAmount);
+///
+/// take_result.assert_constraint_error(ErrorCode::ConstraintSeeds);
+/// take_result.assert_anchor_error_code(6001);
+/// ```
+pub trait AnchorErrorAssertions {
+    /// Get the decoded Anchor error, if the transaction failed with one
+    fn anchor_error(&self) -> Option<AnchorErrorInfo>;
+
+    /// Assert the transaction failed with a specific `#[error_code]` variant
+    ///
+    /// Works with any `#[error_code]` enum, including Anchor's built-in `ErrorCode`.
+    fn assert_anchor_error<E: Into<AnchorLangError>>(&self, expected: E);
+
+    /// Assert the transaction failed with a specific error number
+    fn assert_anchor_error_code(&self, expected: u32);
+
+    /// Assert the transaction failed with a specific account constraint error
+    fn assert_constraint_error(&self, expected: ErrorCode);
+}
+
+impl AnchorErrorAssertions for TransactionError {
+    fn anchor_error(&self) -> Option<AnchorErrorInfo> {
+        self.transaction_error()
+            .and_then(|error| AnchorErrorInfo::decode(error, self.logs()))
+    }
+
+    fn assert_anchor_error<E: Into<AnchorLangError>>(&self, expected: E) {
+        let expected = expected_error(expected);
+        let actual = self.anchor_error().unwrap_or_else(|| {
+            panic!(
+                "Expected Anchor error {}, but transaction failed with: {}\nLogs:\n{}",
+                expected,
+                self,
+                self.logs().join("\n")
+            )
+        });
+
+        assert_eq!(
+            actual.code,
+            expected.code,
+            "Expected Anchor error {}, got {}\nLogs:\n{}",
+            expected,
+            actual,
+            self.logs().join("\n")
+        );
+    }
+
+    fn assert_anchor_error_code(&self, expected: u32) {
+        let actual = self.anchor_error().unwrap_or_else(|| {
+            panic!(
+                "Expected Anchor error {}, but transaction failed with: {}\nLogs:\n{}",
+                expected,
+                self,
+                self.logs().join("\n")
+            )
+        });
+
+        assert_eq!(
+            actual.code,
+            expected,
+            "Expected Anchor error {}, got {}\nLogs:\n{}",
+            expected,
+            actual,
+            self.logs().join("\n")
+        );
+    }
+
+    fn assert_constraint_error(&self, expected: ErrorCode) {
+        let code: u32 = expected.into();
+        assert!(
+            (2000..3000).contains(&code),
+            "{} is not a constraint error code",
+            expected.name()
+        );
+        self.assert_anchor_error(expected);
+    }
+}
+
+impl AnchorErrorAssertions for Result<TransactionResult, TransactionError> {
+    fn anchor_error(&self) -> Option<AnchorErrorInfo> {
+        self.as_ref().err().and_then(|err| err.anchor_error())
+    }
+
+    fn assert_anchor_error<E: Into<AnchorLangError>>(&self, expected: E) {
+        expect_failure(self).assert_anchor_error(expected)
+    }
+
+    fn assert_anchor_error_code(&self, expected: u32) {
+        expect_failure(self).assert_anchor_error_code(expected)
+    }
+
+    fn assert_constraint_error(&self, expected: ErrorCode) {
+        expect_failure(self).assert_constraint_error(expected)
+    }
+}
+
+fn expect_failure(result: &Result<TransactionResult, TransactionError>) -> &TransactionError {
+    match result {
+        Ok(result) => panic!(
+            "Expected transaction to fail, but it succeeded\nLogs:\n{}",
+            result.logs().join("\n")
+        ),
+        Err(err) => err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed(code: u32, logs: &[&str]) -> TransactionError {
+        TransactionError::ExecutionFailed {
+            error: SdkTransactionError::InstructionError(0, InstructionError::Custom(code)),
+            instruction_index: Some(0),
+            logs: logs.iter().map(|log| log.to_string()).collect(),
+            compute_units: 0,
+            inner_instructions: Vec::new(),
+            instruction_data: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_anchor_error_logs() {
+        let info = parse_anchor_error_log(
+            "Program log: AnchorError thrown in programs/anchor_escrow/src/instructions/make.rs:83. Error Code: InvalidAmount. Error Number: 6000. Error Message: Invalid amount.",
+        )
+        .unwrap();
+        assert_eq!(info.code, 6000);
+        assert_eq!(info.name.as_deref(), Some("InvalidAmount"));
+        assert_eq!(info.message.as_deref(), Some("Invalid amount"));
+        assert_eq!(
+            info.origin.as_deref(),
+            Some("programs/anchor_escrow/src/instructions/make.rs:83")
+        );
+
+        let info = parse_anchor_error_log(
+            "Program log: AnchorError caused by account: escrow. Error Code: ConstraintSeeds. Error Number: 2006. Error Message: A seeds constraint was violated.",
+        )
+        .unwrap();
+        assert_eq!(info.code, 2006);
+        assert_eq!(info.origin.as_deref(), Some("escrow"));
+        assert!(info.is_constraint_error());
+
+        assert!(parse_anchor_error_log("Program log: Instruction: Make").is_none());
+    }
+
+    #[test]
+    fn test_decode_without_logs() {
+        let err = failed(6001, &[]);
+        let info = err.anchor_error().unwrap();
+        assert_eq!(info.code, 6001);
+        assert!(info.name.is_none());
+    }
+
+    #[test]
+    fn test_assert_constraint_error() {
+        let err = failed(
+            2006,
+            &["Program log: AnchorError caused by account: escrow. Error Code: ConstraintSeeds. Error Number: 2006. Error Message: A seeds constraint was violated."],
+        );
+        err.assert_constraint_error(ErrorCode::ConstraintSeeds);
+        err.assert_anchor_error(ErrorCode::ConstraintSeeds);
+        err.assert_anchor_error_code(2006);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected Anchor error ConstraintHasOne (2001)")]
+    fn test_assert_anchor_error_mismatch() {
+        failed(2006, &[]).assert_anchor_error(ErrorCode::ConstraintHasOne);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a constraint error code")]
+    fn test_assert_constraint_error_rejects_other_codes() {
+        failed(100, &[]).assert_constraint_error(ErrorCode::InstructionMissing);
+    }
+}
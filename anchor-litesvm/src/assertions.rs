@@ -3,10 +3,10 @@
 //! This module provides convenient assertion methods for verifying
 //! account states in tests.
 
+use crate::account_snapshot::AccountSnapshot;
+use crate::test_helpers::token::unpack_token_account;
 use crate::AnchorContext;
 use solana_program::pubkey::Pubkey;
-use litesvm_token::spl_token;
-use solana_program_pack::Pack;
 
 /// Assertion helper methods for AnchorContext
 pub trait AssertionHelpers {
@@ -38,6 +38,8 @@ pub trait AssertionHelpers {
 
     /// Assert token account balance
     ///
+    /// Works for SPL Token and Token-2022 accounts, including accounts with extensions.
+    ///
     /// # Example
     /// ```no_run
     /// # use anchor_litesvm::{AnchorContext, AssertionHelpers};
@@ -106,6 +108,21 @@ pub trait AssertionHelpers {
     /// ctx.assert_accounts_closed(&[&account1, &account2]);
     /// ```
     fn assert_accounts_closed(&self, pubkeys: &[&Pubkey]);
+
+    /// Assert that an account matches the golden file `snapshots/<name>.snap`
+    ///
+    /// Use [`AccountSnapshot`] directly to redact values that change between runs.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::{AnchorContext, AssertionHelpers};
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// # let escrow_pda = Pubkey::new_unique();
+    /// ctx.assert_account_snapshot("escrow_after_make", &escrow_pda);
+    /// ```
+    fn assert_account_snapshot(&self, name: &str, pubkey: &Pubkey);
 }
 
 impl AssertionHelpers for AnchorContext {
@@ -156,7 +173,7 @@ impl AssertionHelpers for AnchorContext {
                 );
             }
             Some(account) => {
-                let token_state = spl_token::state::Account::unpack(&account.data)
+                let token_state = unpack_token_account(&account.data)
                     .expect(&format!("Failed to unpack token account {}", token_account));
 
                 assert_eq!(
@@ -199,6 +216,10 @@ impl AssertionHelpers for AnchorContext {
             self.assert_account_closed(pubkey);
         }
     }
+
+    fn assert_account_snapshot(&self, name: &str, pubkey: &Pubkey) {
+        AccountSnapshot::new(name).assert(self, pubkey);
+    }
 }
 
 /// Additional assertion functions that don't require self
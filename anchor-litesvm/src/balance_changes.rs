@@ -0,0 +1,348 @@
+//! Before/after balance change assertions
+//!
+//! [`BalanceChanges`] records the lamports and token balances of named
+//! accounts, so that after running instructions the expected deltas can be
+//! checked in one call. On mismatch it panics with a table of every tracked
+//! account instead of the first failing value.
+
+use crate::test_helpers::token::{is_token_program, unpack_token_account};
+use crate::AnchorContext;
+use solana_program::pubkey::Pubkey;
+use std::fmt::Write;
+
+/// Balances of one account at a point in time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Balances {
+    lamports: u64,
+    tokens: u64,
+}
+
+impl Balances {
+    /// Read balances, treating missing or closed accounts as zero
+    ///
+    /// Only accounts owned by a token program have a token balance.
+    fn read(ctx: &AnchorContext, pubkey: &Pubkey) -> Self {
+        ctx.svm
+            .get_account(pubkey)
+            .map(|account| {
+                let tokens = match unpack_token_account(&account.data) {
+                    Ok(token_account) if is_token_program(&account.owner) => token_account.amount,
+                    _ => 0,
+                };
+                Self {
+                    lamports: account.lamports,
+                    tokens,
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Lamports,
+    Tokens,
+}
+
+impl Kind {
+    fn label(self) -> &'static str {
+        match self {
+            Kind::Lamports => "lamports",
+            Kind::Tokens => "tokens",
+        }
+    }
+
+    fn of(self, balances: &Balances) -> u64 {
+        match self {
+            Kind::Lamports => balances.lamports,
+            Kind::Tokens => balances.tokens,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TrackedAccount {
+    name: String,
+    pubkey: Pubkey,
+    before: Balances,
+}
+
+/// Expected balance changes for a set of named accounts
+///
+/// Created with [`AnchorContext::expect_changes`], which records balances
+/// before execution. Accounts that do not exist yet count as zero, so ATAs
+/// created by the instruction can be tracked too.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut changes = ctx.expect_changes(&[
+///     ("maker", maker.pubkey()),
+///     ("maker_ata_b", maker_ata_b),
+///     ("vault", vault),
+///     ("escrow", escrow_pda),
+/// ]);
+///
+/// ctx.instruction_builder("take")
+///     // ...
+///     .execute(&mut ctx, &[&taker])?;
+///
+/// let rent = changes.lamports_before("vault") + changes.lamports_before("escrow");
+/// changes
+///     .tokens("maker_ata_b", 500_000_000)
+///     .lamports("maker", rent as i128)
+///     .verify(&ctx);
+/// ```
+///
+/// On failure the panic message lists every tracked account:
+///
+/// ```text
+/// Balance changes did not match:
+///   account      kind      before      after       expected    actual
+///   maker        lamports  9971464960  9975508560  +4043600    +4043600
+///   maker_ata_b  tokens    0           0           +500000000  0         MISMATCH
+/// ```
+#[derive(Debug, Clone)]
+pub struct BalanceChanges {
+    accounts: Vec<TrackedAccount>,
+    expected: Vec<(usize, Kind, i128)>,
+}
+
+impl BalanceChanges {
+    /// Record the current balances of the given accounts
+    pub fn new(ctx: &AnchorContext, accounts: &[(&str, Pubkey)]) -> Self {
+        let accounts = accounts
+            .iter()
+            .map(|(name, pubkey)| TrackedAccount {
+                name: name.to_string(),
+                pubkey: *pubkey,
+                before: Balances::read(ctx, pubkey),
+            })
+            .collect();
+        Self {
+            accounts,
+            expected: Vec::new(),
+        }
+    }
+
+    /// Expect the lamports of a tracked account to change by `delta`
+    pub fn lamports(&mut self, name: &str, delta: i128) -> &mut Self {
+        let index = self.index_of(name);
+        self.expected.push((index, Kind::Lamports, delta));
+        self
+    }
+
+    /// Expect the token balance of a tracked account to change by `delta`
+    pub fn tokens(&mut self, name: &str, delta: i128) -> &mut Self {
+        let index = self.index_of(name);
+        self.expected.push((index, Kind::Tokens, delta));
+        self
+    }
+
+    /// Lamports of a tracked account when recording started
+    pub fn lamports_before(&self, name: &str) -> u64 {
+        self.accounts[self.index_of(name)].before.lamports
+    }
+
+    /// Token balance of a tracked account when recording started
+    pub fn tokens_before(&self, name: &str) -> u64 {
+        self.accounts[self.index_of(name)].before.tokens
+    }
+
+    /// Actual lamport change of a tracked account so far
+    pub fn lamports_delta(&self, ctx: &AnchorContext, name: &str) -> i128 {
+        self.delta(ctx, self.index_of(name), Kind::Lamports)
+    }
+
+    /// Actual token balance change of a tracked account so far
+    pub fn tokens_delta(&self, ctx: &AnchorContext, name: &str) -> i128 {
+        self.delta(ctx, self.index_of(name), Kind::Tokens)
+    }
+
+    /// Check every expected change, panicking with a diff table on mismatch
+    ///
+    /// Tracked accounts without an expectation are listed in the table but
+    /// never cause a failure.
+    pub fn verify(&self, ctx: &AnchorContext) {
+        let mut rows = Vec::new();
+        let mut failed = false;
+
+        for (index, account) in self.accounts.iter().enumerate() {
+            let after = Balances::read(ctx, &account.pubkey);
+            for kind in [Kind::Lamports, Kind::Tokens] {
+                let expected = self
+                    .expected
+                    .iter()
+                    .find(|(i, k, _)| *i == index && *k == kind)
+                    .map(|(_, _, delta)| *delta);
+                let before = kind.of(&account.before);
+                let actual = kind.of(&after) as i128 - before as i128;
+
+                // Only show token rows for accounts that hold or expect tokens
+                if kind == Kind::Tokens && expected.is_none() && before == 0 && actual == 0 {
+                    continue;
+                }
+
+                let mismatch = expected.is_some_and(|delta| delta != actual);
+                failed |= mismatch;
+                rows.push([
+                    account.name.clone(),
+                    kind.label().to_string(),
+                    before.to_string(),
+                    kind.of(&after).to_string(),
+                    expected.map(signed).unwrap_or_else(|| "-".to_string()),
+                    signed(actual),
+                    if mismatch { "MISMATCH" } else { "" }.to_string(),
+                ]);
+            }
+        }
+
+        if failed {
+            panic!("Balance changes did not match:\n{}", format_table(&rows));
+        }
+    }
+
+    fn delta(&self, ctx: &AnchorContext, index: usize, kind: Kind) -> i128 {
+        let account = &self.accounts[index];
+        let after = Balances::read(ctx, &account.pubkey);
+        kind.of(&after) as i128 - kind.of(&account.before) as i128
+    }
+
+    fn index_of(&self, name: &str) -> usize {
+        self.accounts
+            .iter()
+            .position(|account| account.name == name)
+            .unwrap_or_else(|| panic!("Account `{}` is not tracked by expect_changes", name))
+    }
+}
+
+fn signed(value: i128) -> String {
+    if value > 0 {
+        format!("+{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_table(rows: &[[String; 7]]) -> String {
+    let header = ["account", "kind", "before", "after", "expected", "actual", ""];
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+    let header = header.map(str::to_string);
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        let _ = writeln!(table, "  {}", line.trim_end());
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use litesvm::LiteSVM;
+
+    #[test]
+    fn test_lamport_changes() {
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let funded = Pubkey::new_unique();
+        let untouched = Pubkey::new_unique();
+        ctx.svm.airdrop(&funded, 1_000).unwrap();
+
+        let mut changes = ctx.expect_changes(&[("funded", funded), ("untouched", untouched)]);
+        ctx.svm.airdrop(&funded, 500).unwrap();
+
+        assert_eq!(changes.lamports_before("funded"), 1_000);
+        assert_eq!(changes.lamports_delta(&ctx, "funded"), 500);
+        changes
+            .lamports("funded", 500)
+            .lamports("untouched", 0)
+            .tokens("funded", 0)
+            .verify(&ctx);
+    }
+
+    #[test]
+    fn test_token_balance_requires_token_program_owner() {
+        use solana_program::program_pack::Pack;
+        use solana_sdk::account::Account;
+
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 1_000,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+
+        let mut read = |owner: Pubkey| {
+            let pubkey = Pubkey::new_unique();
+            ctx.svm
+                .set_account(
+                    pubkey,
+                    Account {
+                        lamports: 1_000_000,
+                        data: data.clone(),
+                        owner,
+                        executable: false,
+                        rent_epoch: 0,
+                    },
+                )
+                .unwrap();
+            Balances::read(&ctx, &pubkey).tokens
+        };
+
+        assert_eq!(read(spl_token::id()), 1_000);
+        assert_eq!(read(Pubkey::new_unique()), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Balance changes did not match")]
+    fn test_mismatch_panics() {
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let funded = Pubkey::new_unique();
+
+        let mut changes = ctx.expect_changes(&[("funded", funded)]);
+        ctx.svm.airdrop(&funded, 500).unwrap();
+
+        changes.lamports("funded", -500).verify(&ctx);
+    }
+
+    #[test]
+    fn test_format_table() {
+        let rows = vec![[
+            "maker".to_string(),
+            "lamports".to_string(),
+            "10".to_string(),
+            "5".to_string(),
+            "+5".to_string(),
+            "-5".to_string(),
+            "MISMATCH".to_string(),
+        ]];
+        let table = format_table(&rows);
+        assert_eq!(
+            table,
+            "  account  kind      before  after  expected  actual\n  maker    lamports  10      5      +5        -5      MISMATCH\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Account `missing` is not tracked")]
+    fn test_untracked_account_panics() {
+        let ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let mut changes = ctx.expect_changes(&[]);
+        changes.lamports("missing", 0);
+    }
+}
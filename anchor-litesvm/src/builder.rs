@@ -3,10 +3,73 @@
 //! This module provides a fluent API for setting up test environments
 //! with automatic program deployment and configuration.
 
+use crate::fixtures::{self, AccountFixture, FixtureError};
+use crate::idl::{self, Idl};
+use crate::upgradeable;
+use crate::workspace::{read_program_file, AnchorWorkspace, WorkspaceError};
 use crate::AnchorContext;
 use litesvm::LiteSVM;
+use solana_compute_budget::compute_budget::ComputeBudget;
+use solana_feature_set::FeatureSet;
+use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::{Sysvar, SysvarId};
 use std::collections::HashMap;
+use std::path::Path;
+
+/// Sysvar override applied after the default sysvars are set
+type SysvarOverride = Box<dyn FnOnce(&mut LiteSVM)>;
+
+/// LiteSVM settings applied when the builder creates the VM
+#[derive(Default)]
+struct RuntimeOptions {
+    feature_set: Option<FeatureSet>,
+    sigverify: Option<bool>,
+    blockhash_check: Option<bool>,
+    transaction_history: Option<usize>,
+    log_bytes_limit: Option<Option<usize>>,
+    compute_budget: Option<ComputeBudget>,
+    sysvars: Vec<SysvarOverride>,
+}
+
+impl RuntimeOptions {
+    fn create_svm(self) -> LiteSVM {
+        let mut svm = match self.feature_set {
+            None => LiteSVM::new(),
+            // Builtins and the program runtime are configured from the feature
+            // set, so it has to be in place before `LiteSVM::new()`'s setup runs
+            Some(feature_set) => LiteSVM::default()
+                .with_feature_set(feature_set)
+                .with_builtins()
+                .with_lamports(1_000_000u64.wrapping_mul(LAMPORTS_PER_SOL))
+                .with_sysvars()
+                .with_precompiles()
+                .with_spl_programs()
+                .with_sigverify(true)
+                .with_blockhash_check(true),
+        };
+
+        if let Some(enabled) = self.sigverify {
+            svm = svm.with_sigverify(enabled);
+        }
+        if let Some(enabled) = self.blockhash_check {
+            svm = svm.with_blockhash_check(enabled);
+        }
+        if let Some(capacity) = self.transaction_history {
+            svm = svm.with_transaction_history(capacity);
+        }
+        if let Some(limit) = self.log_bytes_limit {
+            svm = svm.with_log_bytes_limit(limit);
+        }
+        if let Some(compute_budget) = self.compute_budget {
+            svm = svm.with_compute_budget(compute_budget);
+        }
+        for set_sysvar in self.sysvars {
+            set_sysvar(&mut svm);
+        }
+        svm
+    }
+}
 
 /// Builder for creating an AnchorContext with programs pre-deployed
 ///
@@ -29,7 +92,7 @@ use std::collections::HashMap;
 /// let mut ctx = AnchorLiteSVM::build_with_program(program_id, program_bytes);
 /// ```
 pub struct AnchorLiteSVM {
-    svm: LiteSVM,
+    options: RuntimeOptions,
     #[cfg(test)]
     pub(crate) programs: Vec<(Pubkey, Vec<u8>)>,
     #[cfg(not(test))]
@@ -38,15 +101,23 @@ pub struct AnchorLiteSVM {
     pub(crate) primary_program_id: Option<Pubkey>,
     #[cfg(not(test))]
     primary_program_id: Option<Pubkey>,
+    idls: Vec<Idl>,
+    upgradeable_programs: Vec<(Pubkey, Vec<u8>, Pubkey)>,
+    cloned_programs: Vec<(Pubkey, Vec<u8>)>,
+    accounts: Vec<AccountFixture>,
 }
 
 impl AnchorLiteSVM {
     /// Create a new test environment builder
     pub fn new() -> Self {
         Self {
-            svm: LiteSVM::new(),
+            options: RuntimeOptions::default(),
             programs: Vec::new(),
             primary_program_id: None,
+            idls: Vec::new(),
+            upgradeable_programs: Vec::new(),
+            cloned_programs: Vec::new(),
+            accounts: Vec::new(),
         }
     }
 
@@ -94,6 +165,211 @@ impl AnchorLiteSVM {
         self
     }
 
+    /// Deploy a program with the upgradeable BPF loader
+    ///
+    /// Creates the `Program` and `ProgramData` accounts with `upgrade_authority`,
+    /// which `deploy_program` skips. Like `deploy_program`, the first program
+    /// deployed becomes the primary program.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorLiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let program_id = Pubkey::new_unique();
+    /// # let program_bytes = &[];
+    /// let authority = Pubkey::new_unique();
+    /// let mut ctx = AnchorLiteSVM::new()
+    ///     .deploy_upgradeable_program(program_id, program_bytes, authority)
+    ///     .build();
+    /// ```
+    pub fn deploy_upgradeable_program(
+        mut self,
+        program_id: Pubkey,
+        program_bytes: &[u8],
+        upgrade_authority: Pubkey,
+    ) -> Self {
+        if self.primary_program_id.is_none() {
+            self.primary_program_id = Some(program_id);
+        }
+        self.upgradeable_programs
+            .push((program_id, program_bytes.to_vec(), upgrade_authority));
+        self
+    }
+
+    /// Deploy a program from a compiled `.so` file
+    ///
+    /// Use this for programs outside the Anchor workspace; workspace programs
+    /// can be deployed with [`Self::from_anchor_workspace`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorLiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let program_id = Pubkey::new_unique();
+    /// let mut ctx = AnchorLiteSVM::new()
+    ///     .deploy_program_file(program_id, "fixtures/metadata.so")
+    ///     .unwrap()
+    ///     .build();
+    /// ```
+    pub fn deploy_program_file<P: AsRef<Path>>(
+        self,
+        program_id: Pubkey,
+        path: P,
+    ) -> Result<Self, WorkspaceError> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let program_bytes = read_program_file(&name, path)?;
+        Ok(self.deploy_program(program_id, &program_bytes))
+    }
+
+    /// Deploy every program of an Anchor workspace and load the IDLs that were generated
+    ///
+    /// Programs are deployed in name order; the first becomes the primary
+    /// program unless one was already deployed or set.
+    pub fn deploy_workspace(mut self, workspace: &AnchorWorkspace) -> Result<Self, WorkspaceError> {
+        for program in workspace.programs() {
+            let program_bytes = program.read_program()?;
+            if let Some(idl) = program.load_idl()? {
+                idl::idl_program_id(&idl).map_err(|source| WorkspaceError::Idl {
+                    path: program.idl_path.clone(),
+                    source,
+                })?;
+                self.idls.push(idl);
+            }
+            self = self.deploy_program(program.program_id, &program_bytes);
+        }
+        Ok(self)
+    }
+
+    /// Build a context with every program from the surrounding Anchor workspace
+    ///
+    /// Finds `Anchor.toml` in the test crate's directory or a parent, deploys
+    /// each `[programs.localnet]` program from `target/deploy/<name>.so` and
+    /// loads `target/idl/<name>.json` when present. Fails with
+    /// [`WorkspaceError::ProgramNotBuilt`] if `anchor build` has not been run.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorLiteSVM;
+    /// let mut ctx = AnchorLiteSVM::from_anchor_workspace().unwrap();
+    /// ```
+    pub fn from_anchor_workspace() -> Result<AnchorContext, WorkspaceError> {
+        Ok(Self::new()
+            .deploy_workspace(&AnchorWorkspace::discover()?)?
+            .build())
+    }
+
+    /// Preload every `*.json` account fixture in `dir`
+    ///
+    /// Fixtures use the `solana account --output json` format, as written by
+    /// [`AnchorContext::dump_accounts`] or exported from devnet/mainnet.
+    /// Fails if a fixture cannot be read or parsed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorLiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let program_id = Pubkey::new_unique();
+    /// # let program_bytes = &[];
+    /// let mut ctx = AnchorLiteSVM::new()
+    ///     .load_accounts_from_dir("tests/fixtures/escrow")
+    ///     .unwrap()
+    ///     .deploy_program(program_id, program_bytes)
+    ///     .build();
+    /// ```
+    pub fn load_accounts_from_dir<P: AsRef<Path>>(mut self, dir: P) -> Result<Self, FixtureError> {
+        self.accounts.extend(fixtures::read_dir(dir.as_ref())?);
+        Ok(self)
+    }
+
+    /// Deploy a third-party program from a dumped `.so` file
+    ///
+    /// Unlike `deploy_program`, cloned programs never become the primary
+    /// program. Dump mainnet programs with `solana program dump -u m <ID> <FILE>`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorLiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let (program_id, metadata_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+    /// # let program_bytes = &[];
+    /// let mut ctx = AnchorLiteSVM::new()
+    ///     .deploy_program(program_id, program_bytes)
+    ///     .with_cloned_program(metadata_id, "tests/fixtures/clones/programs/metadata.so")
+    ///     .unwrap()
+    ///     .build();
+    /// ```
+    pub fn with_cloned_program<P: AsRef<Path>>(
+        mut self,
+        program_id: Pubkey,
+        so_path: P,
+    ) -> Result<Self, FixtureError> {
+        let program_bytes = fixtures::read_program(so_path.as_ref())?;
+        self.cloned_programs.push((program_id, program_bytes));
+        Ok(self)
+    }
+
+    /// Load a third-party account from a `solana account --output json` file
+    ///
+    /// Fails if the file cannot be read or holds a different account.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorLiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let (program_id, price_feed) = (Pubkey::new_unique(), Pubkey::new_unique());
+    /// # let program_bytes = &[];
+    /// let mut ctx = AnchorLiteSVM::new()
+    ///     .deploy_program(program_id, program_bytes)
+    ///     .with_cloned_account(price_feed, "tests/fixtures/clones/accounts/sol_usd.json")
+    ///     .unwrap()
+    ///     .build();
+    /// ```
+    pub fn with_cloned_account<P: AsRef<Path>>(
+        mut self,
+        pubkey: Pubkey,
+        json_path: P,
+    ) -> Result<Self, FixtureError> {
+        let json_path = json_path.as_ref();
+        let fixture = AccountFixture::read(json_path)?;
+        if fixture.pubkey != pubkey {
+            return Err(FixtureError::Invalid {
+                path: json_path.to_path_buf(),
+                reason: format!("holds account {}, not {}", fixture.pubkey, pubkey),
+            });
+        }
+        self.accounts.push(fixture);
+        Ok(self)
+    }
+
+    /// Load every cloned program and account from an offline clone cache
+    ///
+    /// The cache holds `programs/<PROGRAM_ID>.so` binaries and
+    /// `accounts/<PUBKEY>.json` fixtures; see [`crate::fixtures`] for how to
+    /// fill it from mainnet.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorLiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let program_id = Pubkey::new_unique();
+    /// # let program_bytes = &[];
+    /// let mut ctx = AnchorLiteSVM::new()
+    ///     .deploy_program(program_id, program_bytes)
+    ///     .with_clone_cache("tests/fixtures/clones")
+    ///     .unwrap()
+    ///     .build();
+    /// ```
+    pub fn with_clone_cache<P: AsRef<Path>>(mut self, dir: P) -> Result<Self, FixtureError> {
+        let cache = fixtures::read_clone_cache(dir.as_ref())?;
+        self.cloned_programs.extend(cache.programs);
+        self.accounts.extend(cache.accounts);
+        Ok(self)
+    }
+
     /// Set the primary program ID for the AnchorContext
     ///
     /// By default, the first deployed program becomes the primary program.
@@ -103,13 +379,129 @@ impl AnchorLiteSVM {
         self
     }
 
+    /// Enable or disable transaction signature verification (enabled by default)
+    ///
+    /// Disabling it speeds up tests that send many transactions.
+    pub fn with_sigverify(mut self, enabled: bool) -> Self {
+        self.options.sigverify = Some(enabled);
+        self
+    }
+
+    /// Enable or disable the recent blockhash check (enabled by default)
+    pub fn with_blockhash_check(mut self, enabled: bool) -> Self {
+        self.options.blockhash_check = Some(enabled);
+        self
+    }
+
+    /// Set how many processed transactions are kept for duplicate detection and lookups
+    ///
+    /// `0` disables the history, which also allows resending identical transactions.
+    pub fn with_transaction_history(mut self, capacity: usize) -> Self {
+        self.options.transaction_history = Some(capacity);
+        self
+    }
+
+    /// Limit the bytes of program logs kept per transaction, or `None` for no limit
+    pub fn with_log_bytes_limit(mut self, limit: Option<usize>) -> Self {
+        self.options.log_bytes_limit = Some(limit);
+        self
+    }
+
+    /// Use a fixed compute budget for every transaction
+    ///
+    /// This replaces the budget requested with compute budget instructions.
+    pub fn with_compute_budget(mut self, compute_budget: ComputeBudget) -> Self {
+        self.options.compute_budget = Some(compute_budget);
+        self
+    }
+
+    /// Cap every transaction at `limit` compute units, e.g. 200_000 to mirror
+    /// the mainnet default for a single instruction
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorLiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let program_id = Pubkey::new_unique();
+    /// # let program_bytes = &[];
+    /// let mut ctx = AnchorLiteSVM::new()
+    ///     .deploy_program(program_id, program_bytes)
+    ///     .with_compute_unit_limit(200_000)
+    ///     .with_sigverify(false)
+    ///     .build();
+    /// ```
+    pub fn with_compute_unit_limit(self, limit: u64) -> Self {
+        self.with_compute_budget(ComputeBudget {
+            compute_unit_limit: limit,
+            ..ComputeBudget::default()
+        })
+    }
+
+    /// Run with a specific feature set instead of every feature enabled
+    pub fn with_feature_set(mut self, feature_set: FeatureSet) -> Self {
+        self.options.feature_set = Some(feature_set);
+        self
+    }
+
+    /// Activate a feature gate
+    ///
+    /// Starts from every feature enabled unless a feature set was given.
+    pub fn with_feature(mut self, feature_id: Pubkey) -> Self {
+        self.options
+            .feature_set
+            .get_or_insert_with(FeatureSet::all_enabled)
+            .activate(&feature_id, 0);
+        self
+    }
+
+    /// Deactivate a feature gate, e.g. to test against a cluster that has not activated it yet
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut ctx = AnchorLiteSVM::new()
+    ///     .deploy_program(program_id, program_bytes)
+    ///     .without_feature(solana_feature_set::enable_loader_v4::id())
+    ///     .build();
+    /// ```
+    pub fn without_feature(mut self, feature_id: Pubkey) -> Self {
+        self.options
+            .feature_set
+            .get_or_insert_with(FeatureSet::all_enabled)
+            .deactivate(&feature_id);
+        self
+    }
+
+    /// Override a default sysvar such as `Clock`, `Rent` or `EpochSchedule`
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorLiteSVM;
+    /// # use solana_program::clock::Clock;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let program_id = Pubkey::new_unique();
+    /// # let program_bytes = &[];
+    /// let mut ctx = AnchorLiteSVM::new()
+    ///     .deploy_program(program_id, program_bytes)
+    ///     .with_sysvar(Clock { unix_timestamp: 1_700_000_000, ..Clock::default() })
+    ///     .build();
+    /// ```
+    pub fn with_sysvar<T>(mut self, sysvar: T) -> Self
+    where
+        T: Sysvar + SysvarId + 'static,
+    {
+        self.options
+            .sysvars
+            .push(Box::new(move |svm: &mut LiteSVM| svm.set_sysvar(&sysvar)));
+        self
+    }
+
     /// Build the AnchorContext with all deployed programs
     ///
     /// # Panics
     /// Panics if no programs have been deployed
-    pub fn build(mut self) -> AnchorContext {
+    pub fn build(self) -> AnchorContext {
         assert!(
-            !self.programs.is_empty(),
+            !self.programs.is_empty() || !self.upgradeable_programs.is_empty(),
             "At least one program must be deployed"
         );
 
@@ -117,12 +509,36 @@ impl AnchorLiteSVM {
             .primary_program_id
             .expect("Primary program ID should be set");
 
+        let mut svm = self.options.create_svm();
+
         // Deploy all programs
         for (program_id, program_bytes) in self.programs {
-            self.svm.add_program(program_id, &program_bytes);
+            svm.add_program(program_id, &program_bytes);
+        }
+        for (program_id, program_bytes, upgrade_authority) in self.upgradeable_programs {
+            upgradeable::deploy(&mut svm, program_id, &program_bytes, Some(upgrade_authority))
+                .unwrap_or_else(|e| panic!("{}", e));
+        }
+        for (program_id, program_bytes) in self.cloned_programs {
+            svm.add_program(program_id, &program_bytes);
+        }
+
+        // Accounts last, so fixtures can override program-created state
+        let mut accounts = self.accounts;
+        fixtures::program_data_first(&mut accounts);
+        let fixture_keys: Vec<Pubkey> = accounts.iter().map(|fixture| fixture.pubkey).collect();
+        for fixture in accounts {
+            let pubkey = fixture.pubkey;
+            svm.set_account(pubkey, fixture.account)
+                .unwrap_or_else(|e| panic!("Failed to set account {}: {:?}", pubkey, e));
         }
 
-        AnchorContext::new(self.svm, primary_program_id)
+        let mut ctx = AnchorContext::new(svm, primary_program_id);
+        ctx.track_accounts(fixture_keys);
+        for idl in self.idls {
+            ctx.add_idl(idl).expect("IDL address is validated when added");
+        }
+        ctx
     }
 
     /// Convenience method to create and build with a single program
@@ -260,4 +676,113 @@ mod tests {
         // This should panic because no programs were deployed
         AnchorLiteSVM::new().build();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_deploy_program_file() {
+        let path = std::env::temp_dir().join(format!("anchor_litesvm_{}.so", std::process::id()));
+        let program_id = Pubkey::new_unique();
+
+        let err = AnchorLiteSVM::new()
+            .deploy_program_file(program_id, &path)
+            .err()
+            .unwrap();
+        assert!(matches!(err, WorkspaceError::ProgramNotBuilt { .. }));
+
+        std::fs::write(&path, vec![0u8; 100]).unwrap();
+        let builder = AnchorLiteSVM::new().deploy_program_file(program_id, &path).unwrap();
+        assert_eq!(builder.programs, vec![(program_id, vec![0u8; 100])]);
+        assert_eq!(builder.primary_program_id, Some(program_id));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_cloned_program_is_not_primary() {
+        let path = std::env::temp_dir().join(format!("anchor_litesvm_clone_{}.so", std::process::id()));
+        std::fs::write(&path, vec![0u8; 100]).unwrap();
+        let program_id = Pubkey::new_unique();
+        let cloned_id = Pubkey::new_unique();
+
+        let builder = AnchorLiteSVM::new()
+            .with_cloned_program(cloned_id, &path)
+            .unwrap()
+            .deploy_program(program_id, &[0u8; 100]);
+        assert_eq!(builder.primary_program_id, Some(program_id));
+        assert_eq!(builder.programs.len(), 1);
+        assert_eq!(builder.cloned_programs, vec![(cloned_id, vec![0u8; 100])]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_program_data_fixtures_load_first() {
+        // A dumped upgradeable program whose `Program` fixture comes before its `ProgramData`
+        let program_bytes = LiteSVM::new().get_account(&spl_token::id()).unwrap().data;
+        let program_id = Pubkey::new_unique();
+        let mut source = LiteSVM::new();
+        upgradeable::deploy(&mut source, program_id, &program_bytes, None).unwrap();
+        let fixture = |pubkey| AccountFixture {
+            pubkey,
+            account: source.get_account(&pubkey).unwrap(),
+        };
+
+        let mut builder = AnchorLiteSVM::new().deploy_program(Pubkey::new_unique(), &program_bytes);
+        builder.accounts = vec![
+            fixture(program_id),
+            fixture(upgradeable::program_data_address(&program_id)),
+        ];
+        let ctx = builder.build();
+        assert!(ctx.svm.get_account(&program_id).unwrap().executable);
+    }
+
+    #[test]
+    fn test_cloned_account_mismatch() {
+        let path = std::env::temp_dir().join(format!("anchor_litesvm_clone_{}.json", std::process::id()));
+        let fixture = AccountFixture {
+            pubkey: Pubkey::new_unique(),
+            account: solana_sdk::account::Account::new(1, 0, &Pubkey::default()),
+        };
+        fixture.write(&path).unwrap();
+
+        let err = AnchorLiteSVM::new()
+            .with_cloned_account(Pubkey::new_unique(), &path)
+            .err()
+            .unwrap();
+        assert!(matches!(err, FixtureError::Invalid { .. }));
+        let builder = AnchorLiteSVM::new().with_cloned_account(fixture.pubkey, &path).unwrap();
+        assert_eq!(builder.accounts, vec![fixture]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_runtime_options() {
+        use solana_program::clock::Clock;
+
+        let builder = AnchorLiteSVM::new()
+            .with_sigverify(false)
+            .with_transaction_history(0)
+            .with_sysvar(Clock {
+                slot: 1_000,
+                unix_timestamp: 1_700_000_000,
+                ..Clock::default()
+            });
+        assert_eq!(builder.options.sigverify, Some(false));
+
+        let svm = builder.options.create_svm();
+        let clock = svm.get_sysvar::<Clock>();
+        assert_eq!(clock.slot, 1_000);
+        assert_eq!(clock.unix_timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_without_feature() {
+        let feature_id = solana_feature_set::enable_loader_v4::id();
+        let builder = AnchorLiteSVM::new().without_feature(feature_id);
+
+        // Builtins gated on the feature are not loaded
+        let svm = builder.options.create_svm();
+        assert!(svm.get_account(&solana_program::loader_v4::id()).is_none());
+        assert!(LiteSVM::new().get_account(&solana_program::loader_v4::id()).is_some());
+        // Everything `LiteSVM::new()` sets up is still there
+        assert!(svm.get_account(&spl_token::id()).is_some());
+        assert!(svm.get_account(&solana_program::system_program::id()).is_some());
+    }
+}
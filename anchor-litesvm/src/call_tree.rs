@@ -0,0 +1,473 @@
+//! CPI call tree reconstruction
+//!
+//! The runtime logs `Program <id> invoke [depth]` when a program starts,
+//! `Program <id> consumed <n> of <m> compute units` and then either
+//! `Program <id> success` or `Program <id> failed: <error>` when it returns.
+//! [`CallTree`] rebuilds the nesting of top-level instructions and CPIs from
+//! those lines, so a test can see which call failed or used the most compute.
+
+use crate::program_instruction::to_snake_case;
+use crate::AnchorContext;
+use solana_program::pubkey::Pubkey;
+use std::fmt;
+use std::str::FromStr;
+
+/// How a call in the tree ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallResult {
+    /// The program returned successfully
+    Success,
+    /// The program failed with the logged error
+    Failed(String),
+    /// No result was logged, e.g. because the logs were truncated
+    Incomplete,
+}
+
+/// One program invocation: a top-level instruction or a CPI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallNode {
+    /// The invoked program
+    pub program_id: Pubkey,
+    /// Invocation depth, 1 for top-level instructions
+    pub depth: usize,
+    /// Compute units consumed, including nested calls, if logged
+    pub compute_units: Option<u64>,
+    /// Log lines emitted by this call itself, excluding nested calls
+    pub logs: Vec<String>,
+    /// Snake-case instruction name from the IDL or the `Instruction:` log
+    pub instruction_name: Option<String>,
+    /// Instruction data, known for CPIs recorded as inner instructions and for
+    /// top-level instructions of transactions sent through [`AnchorContext`]
+    pub data: Option<Vec<u8>>,
+    /// How the call ended
+    pub result: CallResult,
+    /// Calls made by this program
+    pub children: Vec<CallNode>,
+}
+
+impl CallNode {
+    fn new(program_id: Pubkey, depth: usize) -> Self {
+        Self {
+            program_id,
+            depth,
+            compute_units: None,
+            logs: Vec::new(),
+            instruction_name: None,
+            data: None,
+            result: CallResult::Incomplete,
+            children: Vec::new(),
+        }
+    }
+
+    /// Whether the call failed
+    pub fn is_failed(&self) -> bool {
+        matches!(self.result, CallResult::Failed(_))
+    }
+
+    /// Compute units consumed by this call, excluding nested calls
+    pub fn self_compute_units(&self) -> Option<u64> {
+        let nested: u64 = self
+            .children
+            .iter()
+            .filter_map(|child| child.compute_units)
+            .sum();
+        self.compute_units.map(|total| total.saturating_sub(nested))
+    }
+
+    /// This call and all nested calls, depth first
+    pub fn iter(&self) -> impl Iterator<Item = &CallNode> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Assign inner instruction data to nested calls in invocation order
+    ///
+    /// Returns false once the data is out of sync with the logs, after which
+    /// nothing else is assigned.
+    fn assign_data<'a>(&mut self, inner: &mut impl Iterator<Item = &'a (u8, Vec<u8>)>) -> bool {
+        for child in &mut self.children {
+            match inner.next() {
+                Some((stack_height, data)) if *stack_height as usize == child.depth => {
+                    child.data = Some(data.clone());
+                    if !child.assign_data(inner) {
+                        return false;
+                    }
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", "  ".repeat(self.depth.saturating_sub(1)), self.program_id)?;
+        if let Some(name) = &self.instruction_name {
+            write!(f, " {}", name)?;
+        }
+        if let Some(units) = self.compute_units {
+            write!(f, " ({} CU)", units)?;
+        }
+        match &self.result {
+            CallResult::Success => writeln!(f, " success")?,
+            CallResult::Failed(error) => writeln!(f, " FAILED: {}", error)?,
+            CallResult::Incomplete => writeln!(f, " incomplete")?,
+        }
+        self.children
+            .iter()
+            .try_for_each(|child| child.fmt_indented(f))
+    }
+}
+
+/// Nested program invocations of a transaction
+///
+/// # Example
+///
+/// ```ignore
+/// let err = ctx.instruction_builder("take")
+///     // ...
+///     .execute(&mut ctx, &[&taker])
+///     .unwrap_err();
+///
+/// let tree = err.call_tree();
+/// let failed = tree.failed().unwrap();
+/// assert_eq!(failed.program_id, spl_token::id());
+/// assert_eq!(failed.instruction_name.as_deref(), Some("transfer_checked"));
+/// println!("{}", tree);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallTree {
+    /// One node per top-level instruction that was logged
+    pub roots: Vec<CallNode>,
+}
+
+impl CallTree {
+    /// Build the tree from transaction logs alone
+    pub fn from_logs(logs: &[String]) -> Self {
+        let mut roots: Vec<CallNode> = Vec::new();
+        let mut stack: Vec<CallNode> = Vec::new();
+
+        fn finish(stack: &mut Vec<CallNode>, roots: &mut Vec<CallNode>, result: CallResult) {
+            if let Some(mut node) = stack.pop() {
+                node.result = result;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => roots.push(node),
+                }
+            }
+        }
+
+        for log in logs {
+            match parse_runtime_log(log) {
+                Some(RuntimeLog::Invoke(program_id, depth)) => {
+                    stack.push(CallNode::new(program_id, depth));
+                }
+                Some(RuntimeLog::Consumed(units)) => {
+                    if let Some(node) = stack.last_mut() {
+                        node.compute_units = Some(units);
+                    }
+                }
+                Some(RuntimeLog::Success) => finish(&mut stack, &mut roots, CallResult::Success),
+                Some(RuntimeLog::Failed(error)) => {
+                    finish(&mut stack, &mut roots, CallResult::Failed(error))
+                }
+                None => {
+                    if let Some(node) = stack.last_mut() {
+                        if node.instruction_name.is_none() {
+                            node.instruction_name = log
+                                .strip_prefix("Program log: Instruction: ")
+                                .map(|name| to_snake_case(name.trim()));
+                        }
+                        node.logs.push(log.clone());
+                    }
+                }
+            }
+        }
+
+        while !stack.is_empty() {
+            finish(&mut stack, &mut roots, CallResult::Incomplete);
+        }
+
+        Self { roots }
+    }
+
+    /// Build the tree from logs, top-level instruction data and per-instruction inner instructions
+    ///
+    /// `instruction_data[i]` is the data of top-level instruction `i`, and
+    /// `inner_instructions[i]` lists `(stack_height, data)` of every CPI it
+    /// made, in invocation order. Either may be empty if unknown.
+    pub(crate) fn from_logs_and_inner(
+        logs: &[String],
+        instruction_data: &[Vec<u8>],
+        inner_instructions: &[Vec<(u8, Vec<u8>)>],
+    ) -> Self {
+        let mut tree = Self::from_logs(logs);
+        for (root, data) in tree.roots.iter_mut().zip(instruction_data) {
+            root.data = Some(data.clone());
+        }
+        for (root, inner) in tree.roots.iter_mut().zip(inner_instructions) {
+            root.assign_data(&mut inner.iter());
+        }
+        tree
+    }
+
+    /// Name calls after the instructions in the IDLs loaded into `ctx`
+    ///
+    /// Only calls with known instruction data to a program with a loaded IDL
+    /// are renamed; other names from `Instruction:` logs are kept.
+    pub fn with_idl_names(mut self, ctx: &AnchorContext) -> Self {
+        fn resolve(node: &mut CallNode, ctx: &AnchorContext) {
+            if let (Some(idl), Some(data)) = (ctx.idl_for(&node.program_id), &node.data) {
+                if let Some(ix) = idl
+                    .instructions
+                    .iter()
+                    .find(|ix| !ix.discriminator.is_empty() && data.starts_with(&ix.discriminator))
+                {
+                    node.instruction_name = Some(ix.name.clone());
+                }
+            }
+            for child in &mut node.children {
+                resolve(child, ctx);
+            }
+        }
+
+        for root in &mut self.roots {
+            resolve(root, ctx);
+        }
+        self
+    }
+
+    /// All calls, depth first
+    pub fn iter(&self) -> impl Iterator<Item = &CallNode> {
+        self.roots.iter().flat_map(CallNode::iter)
+    }
+
+    /// The innermost failed call, which is where the error originated
+    pub fn failed(&self) -> Option<&CallNode> {
+        self.iter()
+            .filter(|node| node.is_failed())
+            .find(|node| !node.children.iter().any(CallNode::is_failed))
+    }
+
+    /// The call that used the most compute units itself, excluding nested calls
+    pub fn most_expensive(&self) -> Option<&CallNode> {
+        self.iter()
+            .filter(|node| node.self_compute_units().is_some())
+            .max_by_key(|node| node.self_compute_units())
+    }
+
+    /// Compute units of each top-level instruction, including its CPIs
+    ///
+    /// Builtin programs such as the system program do not log their usage and
+    /// are reported as 0.
+    pub fn instruction_compute_units(&self) -> Vec<u64> {
+        self.roots
+            .iter()
+            .map(|root| root.compute_units.unwrap_or(0))
+            .collect()
+    }
+
+    /// Compute units used by each program itself, highest first
+    ///
+    /// Every call counts only its own usage, excluding nested calls, so the
+    /// values add up to the sum of [`Self::instruction_compute_units`]. Usage
+    /// of builtin programs called through CPI is counted towards their caller.
+    /// Top-level builtin instructions log no usage and count as 0; their cost
+    /// is reported by [`TransactionResult::unattributed_compute_units`](crate::TransactionResult::unattributed_compute_units).
+    pub fn compute_units_by_program(&self) -> Vec<(Pubkey, u64)> {
+        let mut by_program: Vec<(Pubkey, u64)> = Vec::new();
+        for node in self.iter() {
+            let units = node.self_compute_units().unwrap_or(0);
+            match by_program.iter_mut().find(|(program_id, _)| *program_id == node.program_id) {
+                Some((_, total)) => *total += units,
+                None => by_program.push((node.program_id, units)),
+            }
+        }
+        by_program.sort_by_key(|(_, units)| std::cmp::Reverse(*units));
+        by_program
+    }
+
+    /// All calls to `program_id`
+    pub fn calls_to<'a>(&'a self, program_id: &'a Pubkey) -> impl Iterator<Item = &'a CallNode> {
+        self.iter().filter(move |node| node.program_id == *program_id)
+    }
+}
+
+impl fmt::Display for CallTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.roots.iter().try_for_each(|root| root.fmt_indented(f))
+    }
+}
+
+enum RuntimeLog {
+    Invoke(Pubkey, usize),
+    Consumed(u64),
+    Success,
+    Failed(String),
+}
+
+/// Parse a `Program <id> ...` line written by the runtime itself
+fn parse_runtime_log(log: &str) -> Option<RuntimeLog> {
+    let (program_id, rest) = log.strip_prefix("Program ")?.split_once(' ')?;
+    let program_id = Pubkey::from_str(program_id).ok()?;
+
+    if let Some(depth) = rest.strip_prefix("invoke [") {
+        let depth = depth.strip_suffix(']')?.parse().ok()?;
+        Some(RuntimeLog::Invoke(program_id, depth))
+    } else if let Some(consumed) = rest.strip_prefix("consumed ") {
+        Some(RuntimeLog::Consumed(consumed.split(' ').next()?.parse().ok()?))
+    } else if rest == "success" {
+        Some(RuntimeLog::Success)
+    } else {
+        rest.strip_prefix("failed: ")
+            .map(|error| RuntimeLog::Failed(error.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logs(program: &Pubkey, token: &Pubkey) -> Vec<String> {
+        vec![
+            format!("Program {} invoke [1]", program),
+            "Program log: Instruction: Take".to_string(),
+            format!("Program {} invoke [2]", token),
+            "Program log: Instruction: TransferChecked".to_string(),
+            format!("Program {} consumed 6000 of 190000 compute units", token),
+            format!("Program {} success", token),
+            format!("Program {} invoke [2]", token),
+            "Program log: Instruction: TransferChecked".to_string(),
+            "Program log: Error: insufficient funds".to_string(),
+            format!("Program {} consumed 4000 of 180000 compute units", token),
+            format!("Program {} failed: custom program error: 0x1", token),
+            format!("Program {} consumed 25000 of 200000 compute units", program),
+            format!("Program {} failed: custom program error: 0x1", program),
+        ]
+    }
+
+    #[test]
+    fn test_tree_from_logs() {
+        let program = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let tree = CallTree::from_logs(&logs(&program, &token));
+
+        assert_eq!(tree.roots.len(), 1);
+        let root = &tree.roots[0];
+        assert_eq!(root.instruction_name.as_deref(), Some("take"));
+        assert_eq!(root.compute_units, Some(25_000));
+        assert_eq!(root.self_compute_units(), Some(15_000));
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.children[0].result, CallResult::Success);
+        assert_eq!(root.children[1].depth, 2);
+        assert_eq!(root.logs, vec!["Program log: Instruction: Take".to_string()]);
+
+        let failed = tree.failed().unwrap();
+        assert_eq!(failed.program_id, token);
+        assert_eq!(failed.instruction_name.as_deref(), Some("transfer_checked"));
+        assert!(failed.logs.iter().any(|log| log.contains("insufficient funds")));
+
+        assert_eq!(tree.most_expensive().unwrap().program_id, program);
+        assert_eq!(tree.calls_to(&token).count(), 2);
+        assert_eq!(tree.iter().count(), 3);
+    }
+
+    #[test]
+    fn test_compute_unit_breakdown() {
+        let program = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let mut logs = logs(&program, &token);
+        logs.extend([
+            format!("Program {} invoke [1]", token),
+            format!("Program {} consumed 3000 of 175000 compute units", token),
+            format!("Program {} success", token),
+        ]);
+        let tree = CallTree::from_logs(&logs);
+
+        assert_eq!(tree.instruction_compute_units(), vec![25_000, 3_000]);
+        assert_eq!(
+            tree.compute_units_by_program(),
+            vec![(program, 15_000), (token, 13_000)]
+        );
+    }
+
+    #[test]
+    fn test_inner_instruction_data() {
+        let program = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let inner = vec![vec![(2, vec![12, 1]), (2, vec![12, 2])]];
+        let tree = CallTree::from_logs_and_inner(&logs(&program, &token), &[vec![7]], &inner);
+
+        assert_eq!(tree.roots[0].data, Some(vec![7]));
+        assert_eq!(tree.roots[0].children[0].data, Some(vec![12, 1]));
+        assert_eq!(tree.roots[0].children[1].data, Some(vec![12, 2]));
+    }
+
+    #[test]
+    fn test_idl_names() {
+        use litesvm::LiteSVM;
+
+        let program = Pubkey::new_unique();
+        let mut ctx = AnchorContext::new(LiteSVM::new(), program);
+        let idl = crate::idl::parse_idl(&format!(
+            r#"{{
+                "address": "{}",
+                "metadata": {{ "name": "vault", "version": "0.1.0", "spec": "0.1.0" }},
+                "instructions": [
+                    {{ "name": "deposit_all", "discriminator": [9], "accounts": [], "args": [] }},
+                    {{ "name": "withdraw", "discriminator": [7], "accounts": [], "args": [] }}
+                ]
+            }}"#,
+            program
+        ))
+        .unwrap();
+        ctx.add_idl(idl).unwrap();
+
+        let logs = vec![
+            format!("Program {} invoke [1]", program),
+            format!("Program {} invoke [2]", program),
+            format!("Program {} success", program),
+            format!("Program {} success", program),
+        ];
+        // No `Instruction:` log, so names come from the data alone
+        let tree = CallTree::from_logs_and_inner(&logs, &[vec![7, 0]], &[vec![(2, vec![9, 1])]])
+            .with_idl_names(&ctx);
+
+        assert_eq!(tree.roots[0].instruction_name.as_deref(), Some("withdraw"));
+        assert_eq!(
+            tree.roots[0].children[0].instruction_name.as_deref(),
+            Some("deposit_all")
+        );
+    }
+
+    #[test]
+    fn test_truncated_logs() {
+        let program = Pubkey::new_unique();
+        let tree = CallTree::from_logs(&[
+            format!("Program {} invoke [1]", program),
+            "Log truncated".to_string(),
+        ]);
+
+        assert_eq!(tree.roots.len(), 1);
+        assert_eq!(tree.roots[0].result, CallResult::Incomplete);
+        assert!(tree.failed().is_none());
+    }
+
+    #[test]
+    fn test_display() {
+        let program = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let rendered = CallTree::from_logs(&logs(&program, &token)).to_string();
+        let lines: Vec<_> = rendered.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            format!("{} take (25000 CU) FAILED: custom program error: 0x1", program)
+        );
+        assert_eq!(lines[1], format!("  {} transfer_checked (6000 CU) success", token));
+    }
+}
@@ -0,0 +1,188 @@
+//! anchor-client integration
+//!
+//! [`LiteSvmClient`] backs `RpcClient` with an in-process [`AnchorContext`]
+//! instead of an HTTP connection, so existing `anchor_client::Program` code
+//! (`program.request()...send()`, `program.account::<T>()`) runs against
+//! LiteSVM unchanged. Requests are answered by [`crate::rpc::handle_request`].
+//!
+//! Requires the `anchor-client` feature, which enables anchor-client's `mock`
+//! feature: that is the only way to hand `Client::program` a custom `RpcClient`.
+
+use crate::rpc::{handle_request, SEND_TRANSACTION_PREFLIGHT_FAILURE};
+use crate::AnchorContext;
+use anchor_client::{Client, ClientError, Cluster, Program};
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client::rpc_client::RpcClient as BlockingRpcClient;
+use solana_rpc_client::rpc_client::RpcClientConfig;
+use solana_rpc_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_rpc_client_api::client_error::Result as ClientResult;
+use solana_rpc_client_api::request::{RpcError, RpcRequest, RpcResponseErrorData};
+use solana_rpc_client_api::response::RpcSimulateTransactionResult;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signer;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// An `RpcSender` that answers requests from a shared [`AnchorContext`]
+#[derive(Clone)]
+pub struct LiteSvmSender {
+    ctx: Arc<Mutex<AnchorContext>>,
+}
+
+impl LiteSvmSender {
+    /// Create a sender backed by a shared context
+    pub fn new(ctx: Arc<Mutex<AnchorContext>>) -> Self {
+        Self { ctx }
+    }
+}
+
+#[async_trait]
+impl RpcSender for LiteSvmSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let mut ctx = self.ctx.lock().unwrap_or_else(|e| e.into_inner());
+        handle_request(&mut ctx, &request.to_string(), &params).map_err(|error| {
+            let data = match error.data {
+                Some(data) if error.code == SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+                    serde_json::from_value::<RpcSimulateTransactionResult>(data)
+                        .map(RpcResponseErrorData::SendTransactionPreflightFailure)
+                        .unwrap_or(RpcResponseErrorData::Empty)
+                }
+                _ => RpcResponseErrorData::Empty,
+            };
+            RpcError::RpcResponseError {
+                code: error.code,
+                message: error.message,
+                data,
+            }
+            .into()
+        })
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "litesvm".to_string()
+    }
+}
+
+/// Runs anchor-client programs against an in-process LiteSVM
+///
+/// The context is shared between the client and the test, so assertions can
+/// inspect state after each `send()`.
+///
+/// # Example
+///
+/// ```ignore
+/// use anchor_litesvm::{AnchorLiteSVM, LiteSvmClient};
+/// use std::rc::Rc;
+///
+/// anchor_lang::declare_program!(anchor_escrow);
+///
+/// let ctx = AnchorLiteSVM::build_with_program(anchor_escrow::ID, program_bytes);
+/// let client = LiteSvmClient::new(ctx);
+/// let program = client.program(Rc::new(maker.insecure_clone()), anchor_escrow::ID)?;
+///
+/// program
+///     .request()
+///     .accounts(anchor_escrow::client::accounts::Make { /* ... */ })
+///     .args(anchor_escrow::client::args::Make { seed, receive, amount })
+///     .send()?;
+///
+/// client.context().assert_account_exists(&escrow_pda);
+/// ```
+#[derive(Clone)]
+pub struct LiteSvmClient {
+    ctx: Arc<Mutex<AnchorContext>>,
+}
+
+impl LiteSvmClient {
+    /// Wrap a context so it can be driven through anchor-client
+    pub fn new(ctx: AnchorContext) -> Self {
+        Self {
+            ctx: Arc::new(Mutex::new(ctx)),
+        }
+    }
+
+    /// Lock the shared context for direct access
+    ///
+    /// Drop the guard before sending requests through the client, otherwise
+    /// the request blocks forever waiting for the lock.
+    pub fn context(&self) -> MutexGuard<'_, AnchorContext> {
+        self.ctx.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Get the shared context handle
+    pub fn shared_context(&self) -> Arc<Mutex<AnchorContext>> {
+        Arc::clone(&self.ctx)
+    }
+
+    /// Create a nonblocking `RpcClient` backed by the context
+    pub fn rpc_client(&self) -> RpcClient {
+        RpcClient::new_sender(
+            LiteSvmSender::new(self.shared_context()),
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        )
+    }
+
+    /// Create a blocking `RpcClient` backed by the context
+    pub fn blocking_rpc_client(&self) -> BlockingRpcClient {
+        BlockingRpcClient::new_sender(
+            LiteSvmSender::new(self.shared_context()),
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        )
+    }
+
+    /// Create an anchor-client `Program` that sends through LiteSVM
+    ///
+    /// `payer` pays for and signs every request built from the program.
+    /// The error is boxed since `ClientError` is several hundred bytes.
+    pub fn program<C>(&self, payer: C, program_id: Pubkey) -> Result<Program<C>, Box<ClientError>>
+    where
+        C: Clone + Deref,
+        C::Target: Signer + Sized,
+    {
+        Client::new_with_options(Cluster::Localnet, payer, CommitmentConfig::confirmed())
+            .program(program_id, self.rpc_client())
+            .map_err(Box::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use litesvm::LiteSVM;
+    use solana_sdk::signature::Keypair;
+    use solana_sdk::system_instruction;
+    use solana_sdk::transaction::Transaction;
+
+    #[test]
+    fn test_blocking_rpc_client() {
+        let client = LiteSvmClient::new(AnchorContext::new(LiteSVM::new(), Pubkey::new_unique()));
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        client
+            .context()
+            .svm
+            .airdrop(&payer.pubkey(), 1_000_000_000)
+            .unwrap();
+
+        let rpc = client.blocking_rpc_client();
+        assert_eq!(rpc.get_balance(&payer.pubkey()).unwrap(), 1_000_000_000);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer.pubkey(), &recipient, 1_000_000)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            rpc.get_latest_blockhash().unwrap(),
+        );
+        rpc.send_and_confirm_transaction(&tx).unwrap();
+
+        assert_eq!(rpc.get_account(&recipient).unwrap().lamports, 1_000_000);
+        assert!(rpc.get_account(&Pubkey::new_unique()).is_err());
+    }
+}
@@ -1,10 +1,25 @@
-use crate::account::{get_anchor_account, get_anchor_account_unchecked, AccountError};
-use crate::instruction::build_anchor_instruction;
+use crate::account::{
+    get_anchor_account, get_anchor_account_skip_discriminator, get_anchor_account_unchecked, AccountError,
+};
+use crate::balance_changes::BalanceChanges;
+use crate::fixtures::{self, FixtureError};
+use crate::idl::{self, Idl, IdlError};
+use crate::instruction::{build_anchor_instruction, build_anchor_instruction_with_discriminator};
 use crate::instruction_builder::InstructionBuilder;
-use anchor_lang::{AccountDeserialize, AnchorSerialize};
+use crate::mint_builder::MintBuilder;
+use crate::program_instruction::ProgramInstruction;
+use crate::upgradeable::{self, UpgradeError};
+use anchor_lang::{
+    AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator, InstructionData, ToAccountMetas,
+};
+use litesvm::types::TransactionResult as SvmTransactionResult;
 use litesvm::LiteSVM;
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
 
 /// Wrapper around LiteSVM that provides Anchor-specific utilities
 ///
@@ -16,6 +31,19 @@ pub struct AnchorContext {
     pub svm: LiteSVM,
     /// The Anchor program ID for instruction building
     pub program_id: Pubkey,
+    /// Loaded IDLs, keyed by program ID
+    idls: HashMap<Pubkey, Idl>,
+    /// Addresses the context has seen, since LiteSVM cannot list its accounts
+    known_accounts: BTreeSet<Pubkey>,
+}
+
+/// A saved copy of an [`AnchorContext`]'s chain state
+///
+/// Created with [`AnchorContext::snapshot`] and applied with
+/// [`AnchorContext::restore`].
+#[derive(Clone)]
+pub struct Snapshot {
+    svm: LiteSVM,
 }
 
 impl AnchorContext {
@@ -32,13 +60,55 @@ impl AnchorContext {
     /// let ctx = AnchorContext::new(svm, program_id);
     /// ```
     pub fn new(svm: LiteSVM, program_id: Pubkey) -> Self {
-        Self { svm, program_id }
+        Self {
+            svm,
+            program_id,
+            idls: HashMap::new(),
+            known_accounts: BTreeSet::new(),
+        }
+    }
+
+    /// Load an Anchor IDL file for automatic account resolution
+    ///
+    /// The IDL is registered under the program ID from its `address` field.
+    /// Once loaded, `instruction_builder` fills in constant addresses and
+    /// PDA-derived accounts (including ATAs) for that program's instructions.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorContext;
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// ctx.load_idl("target/idl/anchor_escrow.json").unwrap();
+    /// ```
+    pub fn load_idl<P: AsRef<Path>>(&mut self, path: P) -> Result<(), IdlError> {
+        let idl = idl::load_idl(path)?;
+        self.add_idl(idl)
+    }
+
+    /// Register an already parsed IDL
+    pub fn add_idl(&mut self, idl: Idl) -> Result<(), IdlError> {
+        let program_id = idl::idl_program_id(&idl)?;
+        self.idls.insert(program_id, idl);
+        Ok(())
+    }
+
+    /// Get the IDL loaded for the context's program, if any
+    pub fn idl(&self) -> Option<&Idl> {
+        self.idls.get(&self.program_id)
+    }
+
+    /// Get the IDL loaded for a specific program, if any
+    pub fn idl_for(&self, program_id: &Pubkey) -> Option<&Idl> {
+        self.idls.get(program_id)
     }
 
     /// Build an Anchor instruction with automatic discriminator calculation
     ///
     /// This method handles:
-    /// - Calculating the 8-byte discriminator from the instruction name
+    /// - Using the discriminator from the loaded IDL, or calculating the
+    ///   8-byte discriminator from the instruction name if there is none
     /// - Serializing the instruction arguments using Borsh
     /// - Creating a properly formatted Solana instruction
     ///
@@ -74,21 +144,31 @@ impl AnchorContext {
     where
         T: AnchorSerialize,
     {
-        build_anchor_instruction(&self.program_id, instruction_name, accounts, args)
+        let discriminator = self
+            .idl()
+            .and_then(|idl| idl::find_instruction(idl, instruction_name))
+            .map(|instruction| instruction.discriminator.as_slice());
+        match discriminator {
+            Some(discriminator) => {
+                build_anchor_instruction_with_discriminator(&self.program_id, discriminator, accounts, args)
+            }
+            None => build_anchor_instruction(&self.program_id, instruction_name, accounts, args),
+        }
     }
 
     /// Fetch and deserialize an Anchor account
     ///
-    /// This method retrieves an account from LiteSVM and deserializes it
-    /// using Anchor's AccountDeserialize trait, properly handling the
-    /// 8-byte discriminator.
+    /// This method retrieves an account from LiteSVM, checks its
+    /// discriminator and deserializes it using Anchor's AccountDeserialize
+    /// trait. Custom discriminators of any length are supported; a wrong one
+    /// returns [`AccountError::DiscriminatorMismatch`] with both byte strings.
     ///
     /// # Example
     /// ```no_run
     /// # use anchor_litesvm::AnchorContext;
     /// # use litesvm::LiteSVM;
     /// # use solana_program::pubkey::Pubkey;
-    /// # use anchor_lang::{AccountDeserialize, error::{Error, ErrorCode}};
+    /// # use anchor_lang::{AccountDeserialize, Discriminator, error::{Error, ErrorCode}};
     /// # use borsh::{BorshSerialize, BorshDeserialize};
     /// #
     /// # // Define a simple account structure that implements AccountDeserialize
@@ -98,6 +178,10 @@ impl AnchorContext {
     /// #     amount: u64,
     /// # }
     /// #
+    /// # impl Discriminator for EscrowState {
+    /// #     const DISCRIMINATOR: &'static [u8] = &[0; 8];
+    /// # }
+    /// #
     /// # impl AccountDeserialize for EscrowState {
     /// #     fn try_deserialize(buf: &mut &[u8]) -> Result<Self, Error> {
     /// #         Self::try_deserialize_unchecked(buf)
@@ -115,7 +199,7 @@ impl AnchorContext {
     /// ```
     pub fn get_anchor_account<T>(&self, address: &Pubkey) -> Result<T, AccountError>
     where
-        T: AccountDeserialize,
+        T: AccountDeserialize + Discriminator,
     {
         get_anchor_account(&self.svm, address)
     }
@@ -131,6 +215,17 @@ impl AnchorContext {
         get_anchor_account_unchecked(&self.svm, address)
     }
 
+    /// Fetch and deserialize an Anchor account, skipping `T`'s discriminator without checking it
+    ///
+    /// Unlike [`Self::get_anchor_account_unchecked`], this skips
+    /// `T::DISCRIMINATOR.len()` bytes, so custom discriminators are handled.
+    pub fn get_anchor_account_skip_discriminator<T>(&self, address: &Pubkey) -> Result<T, AccountError>
+    where
+        T: AnchorDeserialize + Discriminator,
+    {
+        get_anchor_account_skip_discriminator(&self.svm, address)
+    }
+
     /// Calculate a program-derived address (PDA)
     ///
     /// Convenience method for PDA calculation using the context's program ID
@@ -160,10 +255,184 @@ impl AnchorContext {
         self.program_id = program_id;
     }
 
+    /// Capture the full state of the test environment
+    ///
+    /// The snapshot includes all accounts (including programs and sysvars),
+    /// the latest blockhash and transaction history. Pass it to [`Self::restore`]
+    /// to rewind, so one expensive setup can be reused across many test branches.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::{AnchorContext, TestHelpers};
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// // Expensive setup: mints, ATAs, funded accounts...
+    /// let maker = ctx.create_funded_account(10_000_000_000).unwrap();
+    /// let snapshot = ctx.snapshot();
+    ///
+    /// // Branch 1: take
+    /// // ...
+    /// ctx.restore(&snapshot);
+    ///
+    /// // Branch 2: refund, starting from the same state
+    /// // ...
+    /// ```
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            svm: self.svm.clone(),
+        }
+    }
+
+    /// Rewind the test environment to a previously captured snapshot
+    ///
+    /// The snapshot can be restored any number of times.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.svm = snapshot.svm.clone();
+    }
+
+    /// Deploy a program with the upgradeable BPF loader
+    ///
+    /// Unlike `svm.add_program`, this creates the `Program` and `ProgramData`
+    /// accounts, so programs that check their upgrade authority (e.g. Anchor
+    /// `program_data` constraints) can run. Redeploying replaces the program.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorContext;
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # use solana_sdk::signature::{Keypair, Signer};
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// # let (program_id, program_bytes) = (Pubkey::new_unique(), vec![]);
+    /// let authority = Keypair::new();
+    /// ctx.deploy_upgradeable_program(program_id, &program_bytes, authority.pubkey()).unwrap();
+    /// ```
+    pub fn deploy_upgradeable_program(
+        &mut self,
+        program_id: Pubkey,
+        program_bytes: &[u8],
+        upgrade_authority: Pubkey,
+    ) -> Result<(), UpgradeError> {
+        upgradeable::deploy(&mut self.svm, program_id, program_bytes, Some(upgrade_authority))
+    }
+
+    /// Replace an upgradeable program's code, keeping its accounts
+    ///
+    /// Fails like the loader's `Upgrade` instruction if `upgrade_authority` is
+    /// not the program's authority or the program is immutable. Use it to test
+    /// account migrations between program versions.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorContext;
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # use solana_sdk::signature::{Keypair, Signer};
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// # let (program_id, v1, v2) = (Pubkey::new_unique(), vec![], vec![]);
+    /// let authority = Keypair::new();
+    /// ctx.deploy_upgradeable_program(program_id, &v1, authority.pubkey()).unwrap();
+    /// // ... create state with v1
+    /// ctx.upgrade_program(program_id, &v2, &authority).unwrap();
+    /// ```
+    pub fn upgrade_program(
+        &mut self,
+        program_id: Pubkey,
+        program_bytes: &[u8],
+        upgrade_authority: &Keypair,
+    ) -> Result<(), UpgradeError> {
+        upgradeable::upgrade(&mut self.svm, program_id, program_bytes, &upgrade_authority.pubkey())
+    }
+
+    /// Get the upgrade authority of an upgradeable program, or `None` if it is immutable
+    pub fn program_upgrade_authority(&self, program_id: &Pubkey) -> Result<Option<Pubkey>, UpgradeError> {
+        upgradeable::upgrade_authority(&self.svm, program_id)
+    }
+
+    /// Remember accounts so [`Self::dump_accounts`] and the RPC
+    /// `getProgramAccounts` method can find them
+    ///
+    /// LiteSVM cannot list its accounts, so the context tracks the account
+    /// keys of every transaction sent through its helpers or the RPC handler,
+    /// accounts created by [`TestHelpers`](crate::TestHelpers) and loaded
+    /// fixtures. Register accounts written directly with `svm.set_account`.
+    pub fn track_accounts(&mut self, pubkeys: impl IntoIterator<Item = Pubkey>) {
+        self.known_accounts.extend(pubkeys);
+    }
+
+    /// Every address the context has seen, including accounts closed since
+    pub fn known_accounts(&self) -> impl Iterator<Item = &Pubkey> {
+        self.known_accounts.iter()
+    }
+
+    /// Send a transaction through LiteSVM and track its account keys
+    #[allow(clippy::result_large_err)] // LiteSVM's own result type
+    pub(crate) fn send_transaction(&mut self, tx: Transaction) -> SvmTransactionResult {
+        self.track_accounts(tx.message.account_keys.iter().copied());
+        self.svm.send_transaction(tx)
+    }
+
+    /// Save every known account as a JSON fixture in `dir`, one `<pubkey>.json` per account
+    ///
+    /// Dumps the live accounts among [`Self::known_accounts`] and returns their
+    /// addresses. Programs, builtins and sysvars are skipped; deploy programs
+    /// separately when loading the fixtures. Files use the
+    /// `solana account --output json` format, so they can be loaded with
+    /// [`Self::load_accounts_from_dir`] or `solana-test-validator --account`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorContext;
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// let saved = ctx.dump_accounts("tests/fixtures/escrow").unwrap();
+    /// ```
+    pub fn dump_accounts<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<Pubkey>, FixtureError> {
+        fixtures::dump_known_accounts(self, dir.as_ref())
+    }
+
+    /// Save specific accounts as JSON fixtures in `dir`
+    ///
+    /// Like [`Self::dump_accounts`], but fails if one of `addresses` does not exist.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorContext;
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// # let (escrow_pda, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+    /// ctx.dump_accounts_at("tests/fixtures/escrow", &[escrow_pda, vault]).unwrap();
+    /// ```
+    pub fn dump_accounts_at<P: AsRef<Path>>(&self, dir: P, addresses: &[Pubkey]) -> Result<(), FixtureError> {
+        fixtures::dump_accounts(self, dir.as_ref(), addresses)
+    }
+
+    /// Load every `*.json` account fixture in `dir`, returning the loaded addresses
+    ///
+    /// Existing accounts at the same addresses are overwritten.
+    pub fn load_accounts_from_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<Vec<Pubkey>, FixtureError> {
+        let mut dir_fixtures = fixtures::read_dir(dir.as_ref())?;
+        fixtures::program_data_first(&mut dir_fixtures);
+        let mut loaded = Vec::new();
+        for fixture in dir_fixtures {
+            loaded.push(fixtures::set_account(self, fixture)?);
+        }
+        Ok(loaded)
+    }
+
+    /// Load a single `solana account --output json` file, returning its address
+    pub fn load_account_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Pubkey, FixtureError> {
+        fixtures::set_account(self, fixtures::AccountFixture::read(path)?)
+    }
+
     /// Create a new instruction builder with fluent API
     ///
     /// This provides a more ergonomic way to build instructions compared to
-    /// manually creating AccountMeta vectors.
+    /// manually creating AccountMeta vectors. If an IDL has been loaded for the
+    /// program, accounts that can be derived from it may be omitted.
     ///
     /// # Example
     /// ```no_run
@@ -184,7 +453,79 @@ impl AnchorContext {
     ///     .unwrap();
     /// ```
     pub fn instruction_builder(&self, instruction_name: &str) -> InstructionBuilder {
-        InstructionBuilder::new(&self.program_id, instruction_name)
+        let builder = InstructionBuilder::new(&self.program_id, instruction_name);
+        match self.idl() {
+            Some(idl) => builder.with_idl(idl),
+            None => builder,
+        }
+    }
+
+    /// Build an instruction from typed Anchor client structs
+    ///
+    /// Takes the `accounts` and `args` structs generated by `declare_program!`
+    /// (or a program crate's `cpi` feature) for this context's program. No
+    /// instruction names or tuple args are involved: the discriminator and
+    /// account order come from the types.
+    ///
+    /// # Example
+    /// ```ignore
+    /// anchor_lang::declare_program!(anchor_escrow);
+    /// use anchor_escrow::client::{accounts, args};
+    ///
+    /// ctx.program_ix(
+    ///     accounts::Make { maker: maker.pubkey(), escrow: escrow_pda, /* ... */ },
+    ///     args::Make { seed, receive: 500_000_000, amount: 1_000_000_000 },
+    /// )
+    /// .execute(&mut ctx, &[&maker])?
+    /// .assert_success();
+    /// ```
+    pub fn program_ix<A, D>(&self, accounts: A, args: D) -> ProgramInstruction
+    where
+        A: ToAccountMetas,
+        D: InstructionData,
+    {
+        ProgramInstruction::new(&self.program_id, accounts, args)
+    }
+
+    /// Create a builder for a Token-2022 mint with extensions
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::{AnchorContext, TestHelpers};
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// let authority = ctx.create_funded_account(10_000_000_000).unwrap();
+    /// let mint = ctx.mint_builder()
+    ///     .decimals(6)
+    ///     .transfer_fee(100, 1_000_000)
+    ///     .create(&mut ctx, &authority)
+    ///     .unwrap();
+    /// ```
+    pub fn mint_builder(&self) -> MintBuilder {
+        MintBuilder::new()
+    }
+
+    /// Record lamport and token balances of named accounts before execution
+    ///
+    /// Returns a [`BalanceChanges`] guard on which the expected deltas are
+    /// declared and checked after the instructions ran.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::AnchorContext;
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// let user = Pubkey::new_unique();
+    /// let mut changes = ctx.expect_changes(&[("user", user)]);
+    ///
+    /// ctx.svm.airdrop(&user, 1_000_000).unwrap();
+    ///
+    /// changes.lamports("user", 1_000_000).verify(&ctx);
+    /// ```
+    pub fn expect_changes(&self, accounts: &[(&str, Pubkey)]) -> BalanceChanges {
+        BalanceChanges::new(self, accounts)
     }
 }
 
@@ -216,4 +557,42 @@ mod tests {
         assert_eq!(pda, expected_pda);
         assert_eq!(bump, expected_bump);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_build_instruction_uses_idl_discriminator() {
+        let mut idl = idl::parse_idl(idl::tests::ESCROW_IDL).unwrap();
+        idl.instructions[0].discriminator = vec![9, 9];
+        let program_id = idl::idl_program_id(&idl).unwrap();
+        let mut ctx = AnchorContext::new(LiteSVM::new(), program_id);
+
+        let ix = ctx.build_instruction("make", vec![], 42u64).unwrap();
+        assert_eq!(ix.data.len(), 8 + 8);
+
+        ctx.add_idl(idl).unwrap();
+        let ix = ctx.build_instruction("make", vec![], 42u64).unwrap();
+        assert_eq!(ix.data, [&[9u8, 9][..], &42u64.to_le_bytes()].concat());
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let user = Pubkey::new_unique();
+        ctx.svm.airdrop(&user, 1_000_000_000).unwrap();
+        let blockhash = ctx.svm.latest_blockhash();
+
+        let snapshot = ctx.snapshot();
+
+        ctx.svm.airdrop(&user, 2_000_000_000).unwrap();
+        ctx.svm.expire_blockhash();
+        assert_eq!(ctx.svm.get_balance(&user), Some(3_000_000_000));
+
+        ctx.restore(&snapshot);
+        assert_eq!(ctx.svm.get_balance(&user), Some(1_000_000_000));
+        assert_eq!(ctx.svm.latest_blockhash(), blockhash);
+
+        // The same snapshot can be restored more than once
+        ctx.svm.airdrop(&user, 5_000_000_000).unwrap();
+        ctx.restore(&snapshot);
+        assert_eq!(ctx.svm.get_balance(&user), Some(1_000_000_000));
+    }
+}
@@ -0,0 +1,272 @@
+//! Compute unit regression baselines
+//!
+//! A [`CuBaseline`] is a JSON file mapping names such as `"escrow::take"` to
+//! the compute units the instruction used when the baseline was recorded. It
+//! is meant to be checked in, so an ordinary `cargo test` run fails as soon as
+//! an instruction becomes more expensive than the recorded value allows.
+//!
+//! Names missing from the file are errors too. Set `UPDATE_CU_BASELINE=1` to
+//! record them and rewrite the recorded values with the current usage instead
+//! of checking them.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Environment variable that makes checks rewrite the baseline
+pub const UPDATE_ENV_VAR: &str = "UPDATE_CU_BASELINE";
+
+/// Default baseline file name, relative to the crate running the tests
+pub const DEFAULT_FILE_NAME: &str = "cu_baseline.json";
+
+/// Serializes read-modify-write cycles of tests running in parallel
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Error, Debug)]
+pub enum CuBaselineError {
+    #[error("Failed to access CU baseline {path}: {source}")]
+    Io {
+        /// The baseline file
+        path: PathBuf,
+        /// The underlying IO error
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse CU baseline {path}: {source}")]
+    Parse {
+        /// The baseline file
+        path: PathBuf,
+        /// The underlying JSON error
+        source: serde_json::Error,
+    },
+
+    #[error(
+        "No compute unit baseline for `{name}` in {path}. Re-run with UPDATE_CU_BASELINE=1 to record it."
+    )]
+    Missing {
+        /// The checked name
+        name: String,
+        /// The baseline file
+        path: PathBuf,
+    },
+
+    #[error(
+        "Compute units for `{name}` regressed: {actual} > baseline {baseline} \
+         (tolerance {tolerance_percent}%). Re-run with UPDATE_CU_BASELINE=1 to accept."
+    )]
+    Regression {
+        /// The checked name
+        name: String,
+        /// The recorded compute units
+        baseline: u64,
+        /// The compute units used now
+        actual: u64,
+        /// The allowed growth in percent
+        tolerance_percent: f64,
+    },
+}
+
+/// A checked-in file of expected compute units
+///
+/// # Example
+///
+/// ```ignore
+/// // Uses `cu_baseline.json` next to the test crate's Cargo.toml
+/// result.record_cu("escrow::take");
+///
+/// // Custom file and tolerance
+/// let baseline = CuBaseline::new("tests/cu.json").tolerance_percent(2.0);
+/// result.record_cu_with(&baseline, "escrow::take");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CuBaseline {
+    path: PathBuf,
+    tolerance_percent: f64,
+    update: Option<bool>,
+}
+
+impl Default for CuBaseline {
+    /// `cu_baseline.json` in `CARGO_MANIFEST_DIR` (or the working directory), with no tolerance
+    fn default() -> Self {
+        let dir = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        Self::new(dir.join(DEFAULT_FILE_NAME))
+    }
+}
+
+impl CuBaseline {
+    /// Use the baseline file at `path` with no tolerance
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            tolerance_percent: 0.0,
+            update: None,
+        }
+    }
+
+    /// Allow usage to grow by up to `percent` over the recorded value
+    pub fn tolerance_percent(mut self, percent: f64) -> Self {
+        self.tolerance_percent = percent;
+        self
+    }
+
+    /// Record values instead of checking them, regardless of `UPDATE_CU_BASELINE`
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = Some(update);
+        self
+    }
+
+    /// The baseline file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the recorded compute units for `name`
+    pub fn get(&self, name: &str) -> Result<Option<u64>, CuBaselineError> {
+        let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(self.read()?.get(name).copied())
+    }
+
+    /// Compare `actual` against the recorded value for `name`
+    ///
+    /// Names without a recorded value are an error. When updating (see
+    /// [`update`](Self::update), `UPDATE_CU_BASELINE` by default), the value
+    /// is recorded or replaced instead. Using fewer compute units than
+    /// recorded never fails.
+    pub fn check(&self, name: &str, actual: u64) -> Result<(), CuBaselineError> {
+        let update = self.update.unwrap_or_else(|| {
+            std::env::var_os(UPDATE_ENV_VAR).is_some_and(|value| value != "0")
+        });
+        self.check_inner(name, actual, update)
+    }
+
+    /// Like [`check`](Self::check), but panics on regression
+    pub fn assert(&self, name: &str, actual: u64) {
+        if let Err(e) = self.check(name, actual) {
+            panic!("{}", e);
+        }
+    }
+
+    fn check_inner(&self, name: &str, actual: u64, update: bool) -> Result<(), CuBaselineError> {
+        let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries = self.read()?;
+
+        match entries.get(name) {
+            Some(&baseline) if !update => {
+                let allowed = baseline as f64 * (1.0 + self.tolerance_percent / 100.0);
+                if actual as f64 > allowed {
+                    return Err(CuBaselineError::Regression {
+                        name: name.to_string(),
+                        baseline,
+                        actual,
+                        tolerance_percent: self.tolerance_percent,
+                    });
+                }
+                Ok(())
+            }
+            None if !update => Err(CuBaselineError::Missing {
+                name: name.to_string(),
+                path: self.path.clone(),
+            }),
+            Some(&baseline) if baseline == actual => Ok(()),
+            _ => {
+                entries.insert(name.to_string(), actual);
+                self.write(&entries)
+            }
+        }
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, u64>, CuBaselineError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|source| CuBaselineError::Parse {
+                path: self.path.clone(),
+                source,
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(source) => Err(CuBaselineError::Io {
+                path: self.path.clone(),
+                source,
+            }),
+        }
+    }
+
+    fn write(&self, entries: &BTreeMap<String, u64>) -> Result<(), CuBaselineError> {
+        let mut json = serde_json::to_string_pretty(entries).map_err(|source| {
+            CuBaselineError::Parse {
+                path: self.path.clone(),
+                source,
+            }
+        })?;
+        json.push('\n');
+        std::fs::write(&self.path, json).map_err(|source| CuBaselineError::Io {
+            path: self.path.clone(),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_baseline(name: &str) -> CuBaseline {
+        let path = std::env::temp_dir().join(format!(
+            "anchor_litesvm_cu_{}_{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        CuBaseline::new(path)
+    }
+
+    #[test]
+    fn test_records_new_entries() {
+        let baseline = temp_baseline("new");
+        baseline.check_inner("escrow::make", 30_000, true).unwrap();
+        baseline.check_inner("escrow::take", 50_000, true).unwrap();
+
+        assert_eq!(baseline.get("escrow::make").unwrap(), Some(30_000));
+        let json = std::fs::read_to_string(baseline.path()).unwrap();
+        assert_eq!(json, "{\n  \"escrow::make\": 30000,\n  \"escrow::take\": 50000\n}\n");
+        std::fs::remove_file(baseline.path()).unwrap();
+    }
+
+    #[test]
+    fn test_regression_and_tolerance() {
+        let baseline = temp_baseline("tolerance").tolerance_percent(5.0);
+        baseline.check_inner("escrow::take", 10_000, true).unwrap();
+
+        // Improvements and growth within tolerance pass without rewriting
+        baseline.check_inner("escrow::take", 9_000, false).unwrap();
+        baseline.check_inner("escrow::take", 10_500, false).unwrap();
+        assert_eq!(baseline.get("escrow::take").unwrap(), Some(10_000));
+
+        let err = baseline.check_inner("escrow::take", 10_501, false).unwrap_err();
+        assert!(matches!(
+            err,
+            CuBaselineError::Regression { baseline: 10_000, actual: 10_501, .. }
+        ));
+        assert!(err.to_string().contains("UPDATE_CU_BASELINE=1"));
+
+        // Updating accepts the new value
+        baseline.check_inner("escrow::take", 10_501, true).unwrap();
+        assert_eq!(baseline.get("escrow::take").unwrap(), Some(10_501));
+        std::fs::remove_file(baseline.path()).unwrap();
+    }
+
+    #[test]
+    fn test_missing_entry() {
+        let baseline = temp_baseline("missing").update(false);
+        let err = baseline.check("escrow::refund", 20_000).unwrap_err();
+        assert!(matches!(err, CuBaselineError::Missing { ref name, .. } if name == "escrow::refund"));
+        assert!(err.to_string().contains("UPDATE_CU_BASELINE=1"));
+        assert!(!baseline.path().exists());
+
+        let baseline = baseline.update(true);
+        baseline.check("escrow::refund", 20_000).unwrap();
+        assert_eq!(baseline.get("escrow::refund").unwrap(), Some(20_000));
+        std::fs::remove_file(baseline.path()).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+//! Anchor event parsing
+//!
+//! Anchor programs emit events in one of two ways:
+//! - `emit!` logs `Program data: <base64>` where the payload is the event
+//!   discriminator followed by the Borsh-serialized event
+//! - `emit_cpi!` invokes the program itself with instruction data
+//!   `EVENT_IX_TAG_LE ++ discriminator ++ event`, which shows up as an inner
+//!   instruction
+//!
+//! Both are decoded here into typed events.
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::Event;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_program::message::inner_instruction::InnerInstruction;
+
+/// Decode all events of type `T` emitted with `emit!`
+pub fn parse_log_events<T: Event>(logs: &[String]) -> Vec<T> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|payload| payload.split_whitespace().next())
+        .filter_map(|encoded| STANDARD.decode(encoded).ok())
+        .filter_map(|data| decode_event(&data))
+        .collect()
+}
+
+/// Decode all events of type `T` emitted with `emit_cpi!`
+///
+/// `instruction_programs` holds the `program_id_index` of each top-level
+/// instruction and `inner_instructions` the CPIs each one made, as recorded in
+/// LiteSVM's transaction metadata. Only self-CPIs, where a program invokes
+/// itself, are decoded, so another program's instruction data that happens to
+/// start with the event tag is never taken for an event.
+pub fn parse_cpi_events<T: Event>(
+    instruction_programs: &[u8],
+    inner_instructions: &[Vec<InnerInstruction>],
+) -> Vec<T> {
+    let mut events = Vec::new();
+    for (program, inner) in instruction_programs.iter().zip(inner_instructions) {
+        // Programs on the invocation stack, indexed by stack height - 1
+        let mut stack = vec![*program];
+        for ix in inner {
+            stack.truncate(usize::from(ix.stack_height.saturating_sub(1)).max(1));
+            let program_id_index = ix.instruction.program_id_index;
+            let is_self_cpi = stack.last() == Some(&program_id_index);
+            stack.push(program_id_index);
+
+            if is_self_cpi {
+                events.extend(
+                    ix.instruction
+                        .data
+                        .strip_prefix(EVENT_IX_TAG_LE)
+                        .and_then(decode_event),
+                );
+            }
+        }
+    }
+    events
+}
+
+/// Decode a single event payload if its discriminator matches `T`
+fn decode_event<T: Event>(data: &[u8]) -> Option<T> {
+    let mut body = data.strip_prefix(T::DISCRIMINATOR)?;
+    T::deserialize(&mut body).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::*;
+
+    #[event]
+    #[derive(Debug, PartialEq)]
+    struct EscrowMade {
+        seed: u64,
+        amount: u64,
+    }
+
+    #[event]
+    #[derive(Debug, PartialEq)]
+    struct EscrowTaken {
+        seed: u64,
+    }
+
+    #[test]
+    fn test_parse_log_events() {
+        let made = EscrowMade { seed: 42, amount: 1_000 };
+        let taken = EscrowTaken { seed: 42 };
+        let logs = vec![
+            "Program log: Instruction: Make".to_string(),
+            format!("Program data: {}", STANDARD.encode(made.data())),
+            format!("Program data: {}", STANDARD.encode(taken.data())),
+            "Program data: not-base64!".to_string(),
+        ];
+
+        assert_eq!(parse_log_events::<EscrowMade>(&logs), vec![made]);
+        assert_eq!(parse_log_events::<EscrowTaken>(&logs), vec![taken]);
+    }
+
+    fn inner(program_id_index: u8, stack_height: u8, data: Vec<u8>) -> InnerInstruction {
+        InnerInstruction {
+            instruction: solana_program::instruction::CompiledInstruction {
+                program_id_index,
+                accounts: vec![],
+                data,
+            },
+            stack_height,
+        }
+    }
+
+    #[test]
+    fn test_parse_cpi_events() {
+        let made = EscrowMade { seed: 7, amount: 500 };
+        let mut cpi_data = EVENT_IX_TAG_LE.to_vec();
+        cpi_data.extend_from_slice(&made.data());
+        let transfer_data = vec![12u8, 0, 0, 0];
+
+        // Program 3 transfers through program 5, then emits an event by invoking itself
+        let inner_instructions = vec![vec![
+            inner(5, 2, transfer_data),
+            inner(3, 2, cpi_data.clone()),
+        ]];
+        let events: Vec<EscrowMade> = parse_cpi_events(&[3], &inner_instructions);
+        assert_eq!(events, vec![made]);
+
+        // The same data sent to another program is not a self-CPI
+        let inner_instructions = vec![vec![inner(5, 2, cpi_data.clone())]];
+        assert!(parse_cpi_events::<EscrowMade>(&[3], &inner_instructions).is_empty());
+
+        // A nested self-CPI is matched against its direct caller
+        let inner_instructions = vec![vec![inner(5, 2, vec![]), inner(5, 3, cpi_data)]];
+        assert_eq!(parse_cpi_events::<EscrowMade>(&[3], &inner_instructions).len(), 1);
+    }
+}
@@ -0,0 +1,479 @@
+//! JSON account fixtures
+//!
+//! Accounts are stored one per file in the format printed by
+//! `solana account <ADDRESS> --output json` and accepted by
+//! `solana-test-validator --account`:
+//!
+//! ```json
+//! {
+//!   "pubkey": "...",
+//!   "account": {
+//!     "lamports": 1461600,
+//!     "data": ["<base64>", "base64"],
+//!     "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+//!     "executable": false,
+//!     "rentEpoch": 18446744073709551615,
+//!     "space": 82
+//!   }
+//! }
+//! ```
+//!
+//! Fixtures dumped from a test, devnet or mainnet can be loaded into another
+//! test without network access.
+//!
+//! Third-party programs and their state can be kept in a clone cache, loaded
+//! with [`AnchorLiteSVM::with_clone_cache`](crate::AnchorLiteSVM::with_clone_cache):
+//!
+//! ```text
+//! tests/fixtures/clones/
+//!   programs/<PROGRAM_ID>.so    solana program dump -u m <PROGRAM_ID> <PROGRAM_ID>.so
+//!   accounts/<PUBKEY>.json      solana account -u m <PUBKEY> --output json -o <PUBKEY>.json
+//! ```
+
+use crate::rpc::{account_json, get_live_account};
+use crate::AnchorContext;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_program::bpf_loader_upgradeable;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar;
+use solana_sdk::account::Account;
+use solana_sdk::native_loader;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Clone cache subdirectory holding `<PROGRAM_ID>.so` program binaries
+pub const CLONED_PROGRAMS_DIR: &str = "programs";
+
+/// Clone cache subdirectory holding `<PUBKEY>.json` account fixtures
+pub const CLONED_ACCOUNTS_DIR: &str = "accounts";
+
+#[derive(Error, Debug)]
+pub enum FixtureError {
+    #[error("Failed to access fixture {path}: {source}")]
+    Io {
+        /// The fixture file or directory
+        path: PathBuf,
+        /// The underlying IO error
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse fixture {path}: {source}")]
+    Parse {
+        /// The fixture file
+        path: PathBuf,
+        /// The underlying JSON error
+        source: serde_json::Error,
+    },
+
+    #[error("Invalid fixture {path}: {reason}")]
+    Invalid {
+        /// The fixture file
+        path: PathBuf,
+        /// What is wrong with it
+        reason: String,
+    },
+
+    #[error("Account {0} does not exist")]
+    AccountNotFound(Pubkey),
+
+    #[error("Failed to set account {0}: {1}")]
+    SetAccount(Pubkey, String),
+}
+
+/// Programs and accounts read from a clone cache
+#[derive(Debug, Default)]
+pub(crate) struct CloneCache {
+    /// `(program ID, program bytes)` for each `programs/<PROGRAM_ID>.so`
+    pub programs: Vec<(Pubkey, Vec<u8>)>,
+    /// The fixtures in `accounts/`
+    pub accounts: Vec<AccountFixture>,
+}
+
+/// An account and its address, as stored in a fixture file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountFixture {
+    pub pubkey: Pubkey,
+    pub account: Account,
+}
+
+impl AccountFixture {
+    /// Render as `solana account --output json` JSON
+    pub fn to_json(&self) -> Value {
+        json!({
+            "pubkey": self.pubkey.to_string(),
+            "account": account_json(&self.account),
+        })
+    }
+
+    /// Parse `solana account --output json` JSON
+    ///
+    /// Data may be encoded as `base64` or `base58`.
+    pub fn from_json(value: &Value) -> Result<Self, String> {
+        let pubkey = value
+            .get("pubkey")
+            .and_then(Value::as_str)
+            .ok_or("missing `pubkey`")?;
+        let pubkey = Pubkey::from_str(pubkey).map_err(|_| format!("invalid pubkey `{}`", pubkey))?;
+        let account = value.get("account").ok_or("missing `account`")?;
+
+        let field = |name: &str| account.get(name).ok_or(format!("missing `account.{}`", name));
+        let lamports = field("lamports")?.as_u64().ok_or("`lamports` is not a number")?;
+        let owner = field("owner")?.as_str().ok_or("`owner` is not a string")?;
+        let owner = Pubkey::from_str(owner).map_err(|_| format!("invalid owner `{}`", owner))?;
+        let executable = field("executable")?.as_bool().ok_or("`executable` is not a bool")?;
+        let rent_epoch = account.get("rentEpoch").and_then(Value::as_u64).unwrap_or(u64::MAX);
+
+        let data = match field("data")?.as_array().map(Vec::as_slice) {
+            Some([Value::String(data), Value::String(encoding)]) => match encoding.as_str() {
+                "base64" => STANDARD.decode(data).map_err(|e| format!("invalid base64 data: {}", e))?,
+                "base58" => bs58::decode(data)
+                    .into_vec()
+                    .map_err(|e| format!("invalid base58 data: {}", e))?,
+                other => return Err(format!("unsupported data encoding `{}`", other)),
+            },
+            _ => return Err("`data` must be [data, encoding]".to_string()),
+        };
+
+        Ok(Self {
+            pubkey,
+            account: Account {
+                lamports,
+                data,
+                owner,
+                executable,
+                rent_epoch,
+            },
+        })
+    }
+
+    /// Read a fixture file
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, FixtureError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|source| FixtureError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let value: Value = serde_json::from_str(&json).map_err(|source| FixtureError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_json(&value).map_err(|reason| FixtureError::Invalid {
+            path: path.to_path_buf(),
+            reason,
+        })
+    }
+
+    /// Write a fixture file
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), FixtureError> {
+        let path = path.as_ref();
+        let mut json = serde_json::to_string_pretty(&self.to_json()).map_err(|source| {
+            FixtureError::Parse {
+                path: path.to_path_buf(),
+                source,
+            }
+        })?;
+        json.push('\n');
+        std::fs::write(path, json).map_err(|source| FixtureError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Write each account to `<dir>/<pubkey>.json`, creating `dir` if needed
+pub(crate) fn dump_accounts(
+    ctx: &AnchorContext,
+    dir: &Path,
+    addresses: &[Pubkey],
+) -> Result<(), FixtureError> {
+    std::fs::create_dir_all(dir).map_err(|source| FixtureError::Io {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    for pubkey in addresses {
+        let account = get_live_account(ctx, pubkey).ok_or(FixtureError::AccountNotFound(*pubkey))?;
+        AccountFixture {
+            pubkey: *pubkey,
+            account,
+        }
+        .write(dir.join(format!("{}.json", pubkey)))?;
+    }
+    Ok(())
+}
+
+/// Write every live account the context has seen, except programs and sysvars
+pub(crate) fn dump_known_accounts(ctx: &AnchorContext, dir: &Path) -> Result<Vec<Pubkey>, FixtureError> {
+    let addresses: Vec<Pubkey> = ctx
+        .known_accounts()
+        .filter(|pubkey| {
+            get_live_account(ctx, pubkey).is_some_and(|account| {
+                !account.executable
+                    && account.owner != sysvar::id()
+                    && account.owner != native_loader::id()
+            })
+        })
+        .copied()
+        .collect();
+    dump_accounts(ctx, dir, &addresses)?;
+    Ok(addresses)
+}
+
+/// Store a fixture's account in the context, returning its address
+pub(crate) fn set_account(ctx: &mut AnchorContext, fixture: AccountFixture) -> Result<Pubkey, FixtureError> {
+    let pubkey = fixture.pubkey;
+    ctx.svm
+        .set_account(pubkey, fixture.account)
+        .map_err(|e| FixtureError::SetAccount(pubkey, format!("{:?}", e)))?;
+    ctx.track_accounts([pubkey]);
+    Ok(pubkey)
+}
+
+/// Move upgradeable loader `ProgramData` fixtures to the front, keeping the order otherwise
+///
+/// LiteSVM compiles an upgradeable program when its `Program` account is set,
+/// which fails unless the `ProgramData` account already exists.
+pub(crate) fn program_data_first(fixtures: &mut [AccountFixture]) {
+    fixtures.sort_by_key(|fixture| !is_program_data(&fixture.account));
+}
+
+fn is_program_data(account: &Account) -> bool {
+    // The `UpgradeableLoaderState::ProgramData` variant index
+    account.owner == bpf_loader_upgradeable::id() && account.data.first() == Some(&3)
+}
+
+/// Read every `*.json` fixture in `dir`, in file name order
+pub(crate) fn read_dir(dir: &Path) -> Result<Vec<AccountFixture>, FixtureError> {
+    list_files(dir, "json")?.iter().map(AccountFixture::read).collect()
+}
+
+/// Read a program binary
+pub(crate) fn read_program(path: &Path) -> Result<Vec<u8>, FixtureError> {
+    std::fs::read(path).map_err(|source| FixtureError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Read the programs and accounts of a clone cache
+///
+/// Missing `programs/` or `accounts/` subdirectories count as empty.
+pub(crate) fn read_clone_cache(dir: &Path) -> Result<CloneCache, FixtureError> {
+    if !dir.is_dir() {
+        return Err(FixtureError::Io {
+            path: dir.to_path_buf(),
+            source: std::io::ErrorKind::NotFound.into(),
+        });
+    }
+
+    let mut cache = CloneCache::default();
+    let programs_dir = dir.join(CLONED_PROGRAMS_DIR);
+    if programs_dir.is_dir() {
+        for path in list_files(&programs_dir, "so")? {
+            let program_id = path
+                .file_stem()
+                .and_then(|stem| Pubkey::from_str(&stem.to_string_lossy()).ok())
+                .ok_or_else(|| FixtureError::Invalid {
+                    path: path.clone(),
+                    reason: "file name is not a program ID".to_string(),
+                })?;
+            cache.programs.push((program_id, read_program(&path)?));
+        }
+    }
+
+    let accounts_dir = dir.join(CLONED_ACCOUNTS_DIR);
+    if accounts_dir.is_dir() {
+        cache.accounts = read_dir(&accounts_dir)?;
+    }
+    Ok(cache)
+}
+
+/// Files in `dir` with the given extension, in name order
+fn list_files(dir: &Path, extension: &str) -> Result<Vec<PathBuf>, FixtureError> {
+    let io_error = |source| FixtureError::Io {
+        path: dir.to_path_buf(),
+        source,
+    };
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        if path.extension().is_some_and(|ext| ext == extension) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TestHelpers, TransactionHelpers};
+    use litesvm::LiteSVM;
+    use solana_sdk::signature::Signer;
+    use solana_sdk::system_instruction;
+
+    #[test]
+    fn test_parse_cli_output() {
+        // Trimmed from `solana account <MINT> --output json`
+        let value = json!({
+            "pubkey": "So11111111111111111111111111111111111111112",
+            "account": {
+                "lamports": 1461600,
+                "data": ["AQID", "base64"],
+                "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "executable": false,
+                "rentEpoch": 18446744073709551615u64,
+                "space": 3
+            }
+        });
+
+        let fixture = AccountFixture::from_json(&value).unwrap();
+        assert_eq!(fixture.pubkey, spl_token::native_mint::id());
+        assert_eq!(fixture.account.data, vec![1, 2, 3]);
+        assert_eq!(fixture.account.owner, spl_token::id());
+        assert_eq!(fixture.account.rent_epoch, u64::MAX);
+        assert_eq!(fixture.to_json(), value);
+
+        let mut base58 = value.clone();
+        base58["account"]["data"] = json!(["Ldp", "base58"]);
+        assert_eq!(AccountFixture::from_json(&base58).unwrap(), fixture);
+
+        let mut zstd = value;
+        zstd["account"]["data"] = json!(["", "base64+zstd"]);
+        assert!(AccountFixture::from_json(&zstd).unwrap_err().contains("base64+zstd"));
+    }
+
+    #[test]
+    fn test_dump_and_load_dir() {
+        let dir = std::env::temp_dir().join(format!("anchor_litesvm_fixtures_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let user = Pubkey::new_unique();
+        let data_account = Pubkey::new_unique();
+        ctx.svm.airdrop(&user, 1_000_000_000).unwrap();
+        ctx.svm
+            .set_account(
+                data_account,
+                Account {
+                    lamports: 2_000_000,
+                    data: vec![7; 40],
+                    owner: ctx.program_id,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
+            .unwrap();
+
+        ctx.dump_accounts_at(&dir, &[user, data_account]).unwrap();
+        assert!(matches!(
+            ctx.dump_accounts_at(&dir, &[Pubkey::new_unique()]),
+            Err(FixtureError::AccountNotFound(_))
+        ));
+
+        let mut other = AnchorContext::new(LiteSVM::new(), ctx.program_id);
+        let mut loaded = other.load_accounts_from_dir(&dir).unwrap();
+        loaded.sort();
+        let mut expected = vec![user, data_account];
+        expected.sort();
+        assert_eq!(loaded, expected);
+        assert_eq!(other.svm.get_account(&user), ctx.svm.get_account(&user));
+        assert_eq!(other.svm.get_account(&data_account), ctx.svm.get_account(&data_account));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dump_known_accounts() {
+        let dir = std::env::temp_dir().join(format!("anchor_litesvm_known_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let payer = ctx.create_funded_account(1_000_000_000).unwrap();
+        let recipient = Pubkey::new_unique();
+        let closed = Pubkey::new_unique();
+        ctx.send_instruction(system_instruction::transfer(&payer.pubkey(), &recipient, 1_000_000), &[&payer])
+            .unwrap();
+        ctx.track_accounts([closed]);
+
+        // The system program is a builtin and the closed account no longer exists
+        let mut saved = ctx.dump_accounts(&dir).unwrap();
+        saved.sort();
+        let mut expected = vec![payer.pubkey(), recipient];
+        expected.sort();
+        assert_eq!(saved, expected);
+
+        let mut other = AnchorContext::new(LiteSVM::new(), ctx.program_id);
+        other.load_accounts_from_dir(&dir).unwrap();
+        assert_eq!(other.svm.get_balance(&recipient), Some(1_000_000));
+        assert!(other.known_accounts().any(|pubkey| *pubkey == recipient));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_upgradeable_program() {
+        let dir = std::env::temp_dir().join(format!("anchor_litesvm_upgradeable_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let program_bytes = ctx.svm.get_account(&spl_token::id()).unwrap().data;
+        let program_id = Pubkey::new_unique();
+        let program_data = crate::program_data_address(&program_id);
+        ctx.deploy_upgradeable_program(program_id, &program_bytes, Pubkey::new_unique())
+            .unwrap();
+        ctx.dump_accounts_at(&dir, &[program_id, program_data]).unwrap();
+
+        let mut fixtures = read_dir(&dir).unwrap();
+        fixtures.sort_by_key(|fixture| fixture.pubkey != program_id);
+        program_data_first(&mut fixtures);
+        assert_eq!(fixtures[0].pubkey, program_data);
+
+        // Loads whichever of the two files sorts first
+        let mut other = AnchorContext::new(LiteSVM::new(), ctx.program_id);
+        other.load_accounts_from_dir(&dir).unwrap();
+        assert!(other.svm.get_account(&program_id).unwrap().executable);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_clone_cache() {
+        let dir = std::env::temp_dir().join(format!("anchor_litesvm_clones_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join(CLONED_PROGRAMS_DIR)).unwrap();
+
+        let program_id = Pubkey::new_unique();
+        std::fs::write(dir.join(CLONED_PROGRAMS_DIR).join(format!("{}.so", program_id)), [1, 2]).unwrap();
+
+        // No accounts/ directory yet
+        let cache = read_clone_cache(&dir).unwrap();
+        assert_eq!(cache.programs, vec![(program_id, vec![1, 2])]);
+        assert!(cache.accounts.is_empty());
+
+        std::fs::create_dir_all(dir.join(CLONED_ACCOUNTS_DIR)).unwrap();
+        let fixture = AccountFixture {
+            pubkey: Pubkey::new_unique(),
+            account: Account {
+                lamports: 1,
+                data: vec![3],
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        };
+        fixture
+            .write(dir.join(CLONED_ACCOUNTS_DIR).join(format!("{}.json", fixture.pubkey)))
+            .unwrap();
+        assert_eq!(read_clone_cache(&dir).unwrap().accounts, vec![fixture]);
+
+        std::fs::write(dir.join(CLONED_PROGRAMS_DIR).join("metaplex.so"), [0]).unwrap();
+        assert!(matches!(read_clone_cache(&dir), Err(FixtureError::Invalid { .. })));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(read_clone_cache(&dir), Err(FixtureError::Io { .. })));
+    }
+}
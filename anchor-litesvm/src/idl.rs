@@ -0,0 +1,758 @@
+//! Anchor IDL loading and automatic account resolution
+//!
+//! This module parses the JSON IDL that `anchor build` writes to
+//! `target/idl/<program>.json` and uses it to fill in instruction accounts
+//! that can be derived without user input:
+//! - Constant addresses (system, token, associated token programs)
+//! - PDAs described by `pda.seeds` (including associated token accounts)
+//!
+//! Explicitly passed accounts are validated against the IDL so that a
+//! misnamed, misordered or wrongly flagged account fails at build time
+//! instead of as an opaque constraint error on-chain.
+
+use anchor_lang_idl_spec::{
+    IdlArrayLen, IdlDefinedFields, IdlInstruction, IdlInstructionAccount,
+    IdlInstructionAccountItem, IdlSeed, IdlSerialization, IdlType, IdlTypeDefTy,
+};
+use serde_json::{Map, Value};
+use solana_program::instruction::AccountMeta;
+use solana_program::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+pub use anchor_lang_idl_spec::Idl;
+
+#[derive(Error, Debug)]
+pub enum IdlError {
+    #[error("Failed to read IDL file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse IDL: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Invalid address in IDL: {0}")]
+    InvalidAddress(String),
+
+    #[error("{0}")]
+    InvalidAccounts(AccountValidationError),
+
+    #[error("Argument '{0}' not found in instruction")]
+    ArgNotFound(String),
+
+    #[error("Unsupported argument type for seed '{0}'")]
+    UnsupportedArgType(String),
+}
+
+/// Load an Anchor IDL from a JSON file (e.g. `target/idl/my_program.json`)
+pub fn load_idl<P: AsRef<Path>>(path: P) -> Result<Idl, IdlError> {
+    let json = std::fs::read_to_string(path)?;
+    parse_idl(&json)
+}
+
+/// Parse an Anchor IDL from a JSON string
+pub fn parse_idl(json: &str) -> Result<Idl, IdlError> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Get the program ID declared in the IDL's `address` field
+pub fn idl_program_id(idl: &Idl) -> Result<Pubkey, IdlError> {
+    Pubkey::from_str(&idl.address).map_err(|_| IdlError::InvalidAddress(idl.address.clone()))
+}
+
+/// Find an instruction in the IDL by its (snake_case) name
+pub fn find_instruction<'a>(idl: &'a Idl, name: &str) -> Option<&'a IdlInstruction> {
+    idl.instructions.iter().find(|ix| ix.name == name)
+}
+
+/// Flatten composite account groups into the order the program expects them
+pub fn flatten_accounts(items: &[IdlInstructionAccountItem]) -> Vec<&IdlInstructionAccount> {
+    let mut accounts = Vec::new();
+    for item in items {
+        match item {
+            IdlInstructionAccountItem::Single(account) => accounts.push(account),
+            IdlInstructionAccountItem::Composite(group) => {
+                accounts.extend(flatten_accounts(&group.accounts))
+            }
+        }
+    }
+    accounts
+}
+
+/// A single problem found when checking builder accounts against the IDL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountIssue {
+    /// A required account was neither passed nor derivable from the IDL
+    Missing { name: String, reason: String },
+    /// An account was passed that the instruction does not declare
+    Extra { name: String },
+    /// An account was passed out of IDL order
+    ///
+    /// Both positions are IDL indices: `actual` is the IDL position of the
+    /// slot the account occupies in call order, `expected` is where it belongs.
+    WrongPosition { name: String, expected: usize, actual: usize },
+    /// The writable flag differs from the IDL
+    WritableMismatch { name: String, expected: bool },
+    /// The signer flag differs from the IDL
+    SignerMismatch { name: String, expected: bool },
+}
+
+impl fmt::Display for AccountIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountIssue::Missing { name, reason } => {
+                write!(f, "missing account '{}': {}", name, reason)
+            }
+            AccountIssue::Extra { name } => {
+                write!(f, "extra account '{}' is not part of the instruction", name)
+            }
+            AccountIssue::WrongPosition { name, expected, actual } => write!(
+                f,
+                "account '{}' is passed in IDL position {} but belongs in position {}",
+                name, actual, expected
+            ),
+            AccountIssue::WritableMismatch { name, expected } => write!(
+                f,
+                "account '{}' should be {}",
+                name,
+                if *expected { "writable" } else { "read-only" }
+            ),
+            AccountIssue::SignerMismatch { name, expected } => write!(
+                f,
+                "account '{}' should {}be a signer",
+                name,
+                if *expected { "" } else { "not " }
+            ),
+        }
+    }
+}
+
+/// All account problems found for one instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountValidationError {
+    pub instruction: String,
+    pub issues: Vec<AccountIssue>,
+}
+
+impl fmt::Display for AccountValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid accounts for instruction '{}':", self.instruction)?;
+        for issue in &self.issues {
+            write!(f, "\n  - {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolve and validate the full account list for an instruction
+///
+/// Accounts passed explicitly always win. Any remaining IDL account is
+/// resolved from its constant `address` or from its `pda` seeds, repeating
+/// until no more progress can be made (a PDA may depend on another PDA).
+///
+/// Explicit accounts are then checked against the IDL: unknown names,
+/// out-of-order calls and writable/signer flag mismatches are all collected
+/// and reported together. Accounts are returned in IDL order, followed by
+/// `remaining` accounts.
+pub(crate) fn resolve_accounts(
+    program_id: &Pubkey,
+    instruction: &IdlInstruction,
+    provided: &[(String, AccountMeta)],
+    remaining: &[AccountMeta],
+    args_data: &[u8],
+) -> Result<Vec<AccountMeta>, IdlError> {
+    let idl_accounts = flatten_accounts(&instruction.accounts);
+    let mut issues = validate_provided(&idl_accounts, provided);
+
+    let mut resolved: HashMap<&str, AccountMeta> = provided
+        .iter()
+        .map(|(name, meta)| (name.as_str(), meta.clone()))
+        .collect();
+
+    loop {
+        let mut progress = false;
+        for account in &idl_accounts {
+            if resolved.contains_key(account.name.as_str()) {
+                continue;
+            }
+            if let Ok(pubkey) = resolve_address(program_id, instruction, account, &resolved, args_data) {
+                resolved.insert(
+                    account.name.as_str(),
+                    AccountMeta {
+                        pubkey,
+                        is_signer: account.signer,
+                        is_writable: account.writable,
+                    },
+                );
+                progress = true;
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+
+    let mut metas = Vec::with_capacity(idl_accounts.len() + remaining.len());
+    for account in &idl_accounts {
+        match resolved.get(account.name.as_str()) {
+            Some(meta) => metas.push(meta.clone()),
+            // Anchor encodes an omitted optional account as the program ID
+            None if account.optional => metas.push(AccountMeta::new_readonly(*program_id, false)),
+            None => {
+                let reason = resolve_address(program_id, instruction, account, &resolved, args_data)
+                    .err()
+                    .unwrap_or_default();
+                issues.push(AccountIssue::Missing {
+                    name: account.name.clone(),
+                    reason,
+                });
+            }
+        }
+    }
+
+    if !issues.is_empty() {
+        return Err(IdlError::InvalidAccounts(AccountValidationError {
+            instruction: instruction.name.clone(),
+            issues,
+        }));
+    }
+
+    metas.extend_from_slice(remaining);
+    Ok(metas)
+}
+
+/// Check explicitly passed accounts for unknown names, ordering and flags
+fn validate_provided(
+    idl_accounts: &[&IdlInstructionAccount],
+    provided: &[(String, AccountMeta)],
+) -> Vec<AccountIssue> {
+    let mut issues = Vec::new();
+    let mut known = Vec::with_capacity(provided.len());
+
+    for (name, meta) in provided {
+        let Some(expected) = idl_accounts.iter().position(|account| &account.name == name) else {
+            issues.push(AccountIssue::Extra { name: name.clone() });
+            continue;
+        };
+        let account = idl_accounts[expected];
+
+        if meta.is_writable != account.writable {
+            issues.push(AccountIssue::WritableMismatch {
+                name: name.clone(),
+                expected: account.writable,
+            });
+        }
+        if meta.is_signer != account.signer {
+            issues.push(AccountIssue::SignerMismatch {
+                name: name.clone(),
+                expected: account.signer,
+            });
+        }
+        known.push((name, expected));
+    }
+
+    // Explicit accounts must appear in the same relative order as the IDL.
+    // Each call-order slot is reported as the IDL position that belongs there.
+    let mut sorted = known.clone();
+    sorted.sort_by_key(|(_, expected)| *expected);
+    for ((name, expected), (sorted_name, slot)) in known.iter().zip(&sorted) {
+        if name != sorted_name {
+            issues.push(AccountIssue::WrongPosition {
+                name: (*name).clone(),
+                expected: *expected,
+                actual: *slot,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Try to derive an account's address, returning a reason on failure
+fn resolve_address(
+    program_id: &Pubkey,
+    instruction: &IdlInstruction,
+    account: &IdlInstructionAccount,
+    resolved: &HashMap<&str, AccountMeta>,
+    args_data: &[u8],
+) -> Result<Pubkey, String> {
+    if let Some(address) = &account.address {
+        return Pubkey::from_str(address).map_err(|_| format!("invalid address '{}'", address));
+    }
+
+    let pda = account
+        .pda
+        .as_ref()
+        .ok_or_else(|| "no constant address or PDA seeds in IDL, pass it explicitly".to_string())?;
+
+    let mut seeds = Vec::with_capacity(pda.seeds.len());
+    for seed in &pda.seeds {
+        seeds.push(seed_bytes(instruction, seed, resolved, args_data)?);
+    }
+
+    let pda_program = match &pda.program {
+        None => *program_id,
+        Some(seed) => {
+            let bytes = seed_bytes(instruction, seed, resolved, args_data)?;
+            Pubkey::try_from(bytes.as_slice())
+                .map_err(|_| "PDA program seed is not a 32-byte address".to_string())?
+        }
+    };
+
+    let seed_refs: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+    Ok(Pubkey::find_program_address(&seed_refs, &pda_program).0)
+}
+
+fn seed_bytes(
+    instruction: &IdlInstruction,
+    seed: &IdlSeed,
+    resolved: &HashMap<&str, AccountMeta>,
+    args_data: &[u8],
+) -> Result<Vec<u8>, String> {
+    match seed {
+        IdlSeed::Const(seed) => Ok(seed.value.clone()),
+        IdlSeed::Arg(seed) => arg_seed_bytes(instruction, &seed.path, args_data).map_err(|e| e.to_string()),
+        IdlSeed::Account(seed) => {
+            if seed.path.contains('.') {
+                return Err(format!(
+                    "seed '{}' reads account data, pass the account explicitly",
+                    seed.path
+                ));
+            }
+            resolved
+                .get(seed.path.as_str())
+                .map(|meta| meta.pubkey.to_bytes().to_vec())
+                .ok_or_else(|| format!("depends on unresolved account '{}'", seed.path))
+        }
+    }
+}
+
+/// Extract the raw seed bytes of a top-level instruction argument
+///
+/// Anchor seeds an argument with its little-endian / byte representation,
+/// which matches Borsh for fixed-size types. Strings and byte vectors are
+/// seeded without their 4-byte length prefix.
+fn arg_seed_bytes(instruction: &IdlInstruction, path: &str, args_data: &[u8]) -> Result<Vec<u8>, IdlError> {
+    let mut offset = 0;
+    for arg in &instruction.args {
+        let remaining = args_data.get(offset..).unwrap_or_default();
+        let len = borsh_len(&arg.ty, remaining).ok_or_else(|| IdlError::UnsupportedArgType(arg.name.clone()))?;
+        if arg.name == path {
+            let bytes = remaining
+                .get(..len)
+                .ok_or_else(|| IdlError::ArgNotFound(path.to_string()))?;
+            return Ok(match arg.ty {
+                IdlType::String | IdlType::Bytes => bytes[4..].to_vec(),
+                _ => bytes.to_vec(),
+            });
+        }
+        offset += len;
+    }
+    Err(IdlError::ArgNotFound(path.to_string()))
+}
+
+/// Compute the Borsh-encoded length of a value of the given IDL type
+fn borsh_len(ty: &IdlType, data: &[u8]) -> Option<usize> {
+    let fixed = match ty {
+        IdlType::Bool | IdlType::U8 | IdlType::I8 => Some(1),
+        IdlType::U16 | IdlType::I16 => Some(2),
+        IdlType::U32 | IdlType::I32 | IdlType::F32 => Some(4),
+        IdlType::U64 | IdlType::I64 | IdlType::F64 => Some(8),
+        IdlType::U128 | IdlType::I128 => Some(16),
+        IdlType::U256 | IdlType::I256 | IdlType::Pubkey => Some(32),
+        _ => None,
+    };
+    if fixed.is_some() {
+        return fixed;
+    }
+
+    match ty {
+        IdlType::String | IdlType::Bytes => {
+            let len = read_u32(data)? as usize;
+            Some(4 + len)
+        }
+        IdlType::Option(inner) => match data.first()? {
+            0 => Some(1),
+            _ => Some(1 + borsh_len(inner, data.get(1..)?)?),
+        },
+        IdlType::Vec(inner) => {
+            let count = read_u32(data)? as usize;
+            let mut offset = 4;
+            for _ in 0..count {
+                offset += borsh_len(inner, data.get(offset..)?)?;
+            }
+            Some(offset)
+        }
+        IdlType::Array(inner, IdlArrayLen::Value(count)) => {
+            let mut offset = 0;
+            for _ in 0..*count {
+                offset += borsh_len(inner, data.get(offset..)?)?;
+            }
+            Some(offset)
+        }
+        _ => None,
+    }
+}
+
+fn read_u32(data: &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(..4)?.try_into().ok()?))
+}
+
+/// Decode Anchor account data into JSON using the IDL's account and type definitions
+///
+/// The account type is picked by discriminator. Returns the account name and
+/// its fields, or `None` if no IDL account matches or the data does not
+/// decode (for example zero-copy accounts). Integers wider than 64 bits are
+/// rendered as strings, `bytes` as hex.
+pub fn decode_account<'a>(idl: &'a Idl, data: &[u8]) -> Option<(&'a str, Value)> {
+    let account = idl
+        .accounts
+        .iter()
+        .find(|account| !account.discriminator.is_empty() && data.starts_with(&account.discriminator))?;
+    let mut rest = &data[account.discriminator.len()..];
+    let value = decode_defined(idl, &account.name, &mut rest)?;
+    Some((&account.name, value))
+}
+
+fn decode_defined(idl: &Idl, name: &str, data: &mut &[u8]) -> Option<Value> {
+    let def = idl.types.iter().find(|def| def.name == name)?;
+    if !matches!(def.serialization, IdlSerialization::Borsh) {
+        return None;
+    }
+
+    match &def.ty {
+        IdlTypeDefTy::Struct { fields } => decode_fields(idl, fields.as_ref(), data),
+        IdlTypeDefTy::Enum { variants } => {
+            let variant = variants.get(take(data, 1)?[0] as usize)?;
+            match &variant.fields {
+                None => Some(Value::String(variant.name.clone())),
+                Some(fields) => {
+                    let mut map = Map::new();
+                    map.insert(variant.name.clone(), decode_fields(idl, Some(fields), data)?);
+                    Some(Value::Object(map))
+                }
+            }
+        }
+        IdlTypeDefTy::Type { alias } => decode_value(idl, alias, data),
+    }
+}
+
+fn decode_fields(idl: &Idl, fields: Option<&IdlDefinedFields>, data: &mut &[u8]) -> Option<Value> {
+    match fields {
+        None => Some(Value::Object(Map::new())),
+        Some(IdlDefinedFields::Named(fields)) => {
+            let mut map = Map::new();
+            for field in fields {
+                map.insert(field.name.clone(), decode_value(idl, &field.ty, data)?);
+            }
+            Some(Value::Object(map))
+        }
+        Some(IdlDefinedFields::Tuple(types)) => types
+            .iter()
+            .map(|ty| decode_value(idl, ty, data))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+    }
+}
+
+fn decode_value(idl: &Idl, ty: &IdlType, data: &mut &[u8]) -> Option<Value> {
+    Some(match ty {
+        IdlType::Bool => Value::Bool(take(data, 1)?[0] != 0),
+        IdlType::U8 => take(data, 1)?[0].into(),
+        IdlType::I8 => (take(data, 1)?[0] as i8).into(),
+        IdlType::U16 => u16::from_le_bytes(take_array(data)?).into(),
+        IdlType::I16 => i16::from_le_bytes(take_array(data)?).into(),
+        IdlType::U32 => u32::from_le_bytes(take_array(data)?).into(),
+        IdlType::I32 => i32::from_le_bytes(take_array(data)?).into(),
+        IdlType::U64 => u64::from_le_bytes(take_array(data)?).into(),
+        IdlType::I64 => i64::from_le_bytes(take_array(data)?).into(),
+        IdlType::F32 => f32::from_le_bytes(take_array(data)?).into(),
+        IdlType::F64 => f64::from_le_bytes(take_array(data)?).into(),
+        IdlType::U128 => u128::from_le_bytes(take_array(data)?).to_string().into(),
+        IdlType::I128 => i128::from_le_bytes(take_array(data)?).to_string().into(),
+        IdlType::U256 | IdlType::I256 => hex(take(data, 32)?).into(),
+        IdlType::Pubkey => Pubkey::new_from_array(take_array(data)?).to_string().into(),
+        IdlType::Bytes => {
+            let len = u32::from_le_bytes(take_array(data)?) as usize;
+            hex(take(data, len)?).into()
+        }
+        IdlType::String => {
+            let len = u32::from_le_bytes(take_array(data)?) as usize;
+            String::from_utf8(take(data, len)?.to_vec()).ok()?.into()
+        }
+        IdlType::Option(inner) => match take(data, 1)?[0] {
+            0 => Value::Null,
+            _ => decode_value(idl, inner, data)?,
+        },
+        IdlType::Vec(inner) => {
+            let count = u32::from_le_bytes(take_array(data)?);
+            (0..count)
+                .map(|_| decode_value(idl, inner, data))
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Array)?
+        }
+        IdlType::Array(inner, IdlArrayLen::Value(count)) => (0..*count)
+            .map(|_| decode_value(idl, inner, data))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array)?,
+        IdlType::Defined { name, .. } => decode_defined(idl, name, data)?,
+        _ => return None,
+    })
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if data.len() < len {
+        return None;
+    }
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Some(head)
+}
+
+fn take_array<const N: usize>(data: &mut &[u8]) -> Option<[u8; N]> {
+    take(data, N)?.try_into().ok()
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Trimmed IDL for the escrow program's `make` instruction
+    pub(crate) const ESCROW_IDL: &str = r#"{
+        "address": "8LTee82TkoqBoBjBAz2yAAKSj9ckr7zz5vMi6rJQTwhJ",
+        "metadata": { "name": "anchor_escrow", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": [
+            {
+                "name": "make",
+                "discriminator": [138, 227, 232, 77, 223, 166, 96, 197],
+                "accounts": [
+                    { "name": "maker", "writable": true, "signer": true },
+                    {
+                        "name": "escrow",
+                        "writable": true,
+                        "pda": {
+                            "seeds": [
+                                { "kind": "const", "value": [101, 115, 99, 114, 111, 119] },
+                                { "kind": "account", "path": "maker" },
+                                { "kind": "arg", "path": "seed" }
+                            ]
+                        }
+                    },
+                    { "name": "mint_a" },
+                    { "name": "mint_b" },
+                    {
+                        "name": "maker_ata_a",
+                        "writable": true,
+                        "pda": {
+                            "seeds": [
+                                { "kind": "account", "path": "maker" },
+                                { "kind": "account", "path": "token_program" },
+                                { "kind": "account", "path": "mint_a" }
+                            ],
+                            "program": {
+                                "kind": "const",
+                                "value": [140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89]
+                            }
+                        }
+                    },
+                    {
+                        "name": "vault",
+                        "writable": true,
+                        "pda": {
+                            "seeds": [
+                                { "kind": "account", "path": "escrow" },
+                                { "kind": "account", "path": "token_program" },
+                                { "kind": "account", "path": "mint_a" }
+                            ],
+                            "program": {
+                                "kind": "const",
+                                "value": [140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89]
+                            }
+                        }
+                    },
+                    { "name": "associated_token_program", "address": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL" },
+                    { "name": "token_program" },
+                    { "name": "system_program", "address": "11111111111111111111111111111111" }
+                ],
+                "args": [
+                    { "name": "seed", "type": "u64" },
+                    { "name": "receive", "type": "u64" },
+                    { "name": "amount", "type": "u64" }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_idl() {
+        let idl = parse_idl(ESCROW_IDL).unwrap();
+        assert_eq!(idl.metadata.name, "anchor_escrow");
+        assert_eq!(
+            idl_program_id(&idl).unwrap(),
+            Pubkey::from_str("8LTee82TkoqBoBjBAz2yAAKSj9ckr7zz5vMi6rJQTwhJ").unwrap()
+        );
+
+        let make = find_instruction(&idl, "make").unwrap();
+        assert_eq!(flatten_accounts(&make.accounts).len(), 9);
+        assert!(find_instruction(&idl, "take").is_none());
+    }
+
+    #[test]
+    fn test_resolve_make_accounts() {
+        let idl = parse_idl(ESCROW_IDL).unwrap();
+        let program_id = idl_program_id(&idl).unwrap();
+        let make = find_instruction(&idl, "make").unwrap();
+
+        let maker = Pubkey::new_unique();
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+        let provided = vec![
+            ("maker".to_string(), AccountMeta::new(maker, true)),
+            ("mint_a".to_string(), AccountMeta::new_readonly(mint_a, false)),
+            ("mint_b".to_string(), AccountMeta::new_readonly(mint_b, false)),
+            ("token_program".to_string(), AccountMeta::new_readonly(spl_token::id(), false)),
+        ];
+
+        let seed = 42u64;
+        let mut args = seed.to_le_bytes().to_vec();
+        args.extend_from_slice(&1u64.to_le_bytes());
+        args.extend_from_slice(&2u64.to_le_bytes());
+
+        let metas = resolve_accounts(&program_id, make, &provided, &[], &args).unwrap();
+        assert_eq!(metas.len(), 9);
+
+        let (escrow, _) = Pubkey::find_program_address(
+            &[b"escrow", maker.as_ref(), &seed.to_le_bytes()],
+            &program_id,
+        );
+        assert_eq!(metas[1].pubkey, escrow);
+        assert!(metas[1].is_writable);
+        assert_eq!(
+            metas[4].pubkey,
+            spl_associated_token_account::get_associated_token_address(&maker, &mint_a)
+        );
+        assert_eq!(
+            metas[5].pubkey,
+            spl_associated_token_account::get_associated_token_address(&escrow, &mint_a)
+        );
+        assert_eq!(metas[6].pubkey, spl_associated_token_account::id());
+        assert_eq!(metas[8].pubkey, solana_program::system_program::id());
+    }
+
+    #[test]
+    fn test_unresolved_account_error() {
+        let idl = parse_idl(ESCROW_IDL).unwrap();
+        let program_id = idl_program_id(&idl).unwrap();
+        let make = find_instruction(&idl, "make").unwrap();
+
+        let err = resolve_accounts(&program_id, make, &[], &[], &[0u8; 24]).unwrap_err();
+        let IdlError::InvalidAccounts(err) = err else {
+            panic!("expected InvalidAccounts, got {:?}", err);
+        };
+        assert!(matches!(&err.issues[0], AccountIssue::Missing { name, .. } if name == "maker"));
+        assert!(err.to_string().contains("missing account 'maker'"));
+    }
+
+    #[test]
+    fn test_validation_reports_all_issues() {
+        let idl = parse_idl(ESCROW_IDL).unwrap();
+        let program_id = idl_program_id(&idl).unwrap();
+        let make = find_instruction(&idl, "make").unwrap();
+
+        let maker = Pubkey::new_unique();
+        let provided = vec![
+            ("maker".to_string(), AccountMeta::new(maker, true)),
+            ("vault".to_string(), AccountMeta::new(Pubkey::new_unique(), false)),
+            ("maker_ata_a".to_string(), AccountMeta::new(Pubkey::new_unique(), false)),
+            ("mint_a".to_string(), AccountMeta::new(Pubkey::new_unique(), false)),
+            ("mint_b".to_string(), AccountMeta::new_readonly(Pubkey::new_unique(), true)),
+            ("bogus".to_string(), AccountMeta::new_readonly(Pubkey::new_unique(), false)),
+            ("token_program".to_string(), AccountMeta::new_readonly(spl_token::id(), false)),
+        ];
+
+        let err = resolve_accounts(&program_id, make, &provided, &[], &[0u8; 24]).unwrap_err();
+        let IdlError::InvalidAccounts(err) = err else {
+            panic!("expected InvalidAccounts, got {:?}", err);
+        };
+
+        assert!(err.issues.contains(&AccountIssue::Extra { name: "bogus".to_string() }));
+        assert!(err.issues.contains(&AccountIssue::WritableMismatch {
+            name: "mint_a".to_string(),
+            expected: false,
+        }));
+        assert!(err.issues.contains(&AccountIssue::SignerMismatch {
+            name: "mint_b".to_string(),
+            expected: false,
+        }));
+        // vault and maker_ata_a were swapped, so mint_a/mint_b are out of place too
+        assert!(err.issues.iter().any(|issue| matches!(
+            issue,
+            AccountIssue::WrongPosition { name, expected: 5, actual: 2 } if name == "vault"
+        )));
+        assert!(err.issues.iter().any(|issue| matches!(
+            issue,
+            AccountIssue::WrongPosition { name, expected: 4, actual: 3 } if name == "maker_ata_a"
+        )));
+    }
+
+    #[test]
+    fn test_decode_account() {
+        let idl = parse_idl(
+            r#"{
+                "address": "8LTee82TkoqBoBjBAz2yAAKSj9ckr7zz5vMi6rJQTwhJ",
+                "metadata": { "name": "anchor_escrow", "version": "0.1.0", "spec": "0.1.0" },
+                "instructions": [],
+                "accounts": [{ "name": "Escrow", "discriminator": [1] }],
+                "types": [
+                    {
+                        "name": "Escrow",
+                        "type": {
+                            "kind": "struct",
+                            "fields": [
+                                { "name": "seed", "type": "u64" },
+                                { "name": "maker", "type": "pubkey" },
+                                { "name": "memo", "type": { "option": "string" } },
+                                { "name": "status", "type": { "defined": { "name": "Status" } } },
+                                { "name": "bump", "type": "u8" }
+                            ]
+                        }
+                    },
+                    {
+                        "name": "Status",
+                        "type": { "kind": "enum", "variants": [{ "name": "Open" }, { "name": "Taken" }] }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let maker = Pubkey::new_unique();
+        let mut data = vec![1];
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(maker.as_ref());
+        data.extend_from_slice(&[1, 2, 0, 0, 0, b'h', b'i']);
+        data.extend_from_slice(&[1, 254]);
+
+        let (name, value) = decode_account(&idl, &data).unwrap();
+        assert_eq!(name, "Escrow");
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "seed": 42,
+                "maker": maker.to_string(),
+                "memo": "hi",
+                "status": "Taken",
+                "bump": 254
+            })
+        );
+
+        assert!(decode_account(&idl, &[2, 0, 0]).is_none());
+        assert!(decode_account(&idl, &data[..20]).is_none());
+    }
+}
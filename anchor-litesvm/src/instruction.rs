@@ -9,6 +9,9 @@ use solana_program::pubkey::Pubkey;
 /// - Calculates the 8-byte discriminator from the instruction name
 /// - Serializes the instruction arguments using Borsh
 /// - Combines them into the complete instruction data
+///
+/// For instructions declared with `#[instruction(discriminator = ...)]`, use
+/// [`build_anchor_instruction_with_discriminator`].
 pub fn build_anchor_instruction<T>(
     program_id: &Pubkey,
     instruction_name: &str,
@@ -20,7 +23,22 @@ where
 {
     // Calculate discriminator using Anchor's method: sha256("global:<instruction_name>")[..8]
     let discriminator = calculate_anchor_discriminator(instruction_name);
+    build_anchor_instruction_with_discriminator(program_id, &discriminator, accounts, args)
+}
 
+/// Builds an Anchor instruction with an explicit discriminator
+///
+/// The discriminator may be any length, e.g. a custom one from the program's
+/// IDL (`instruction.discriminator`).
+pub fn build_anchor_instruction_with_discriminator<T>(
+    program_id: &Pubkey,
+    discriminator: &[u8],
+    accounts: Vec<AccountMeta>,
+    args: T,
+) -> Result<Instruction, Box<dyn std::error::Error>>
+where
+    T: AnchorSerialize,
+{
     // Serialize the instruction arguments
     let mut data = discriminator.to_vec();
     args.serialize(&mut data)?;
@@ -97,4 +115,17 @@ mod tests {
         assert_eq!(instruction.accounts.len(), 2);
         assert!(instruction.data.len() >= 8); // At least discriminator
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_custom_discriminator() {
+        let instruction = build_anchor_instruction_with_discriminator(
+            &Pubkey::new_unique(),
+            &[7],
+            vec![],
+            42u64,
+        )
+        .unwrap();
+
+        assert_eq!(instruction.data, vec![7, 42, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
@@ -1,6 +1,8 @@
+use crate::idl::{self, Idl};
 use crate::instruction::calculate_anchor_discriminator;
-use crate::transaction::{TransactionError, TransactionResult};
+use crate::transaction::{TransactionError, TransactionHelpers, TransactionResult};
 use anchor_lang::AnchorSerialize;
+use anchor_lang_idl_spec::IdlInstruction;
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
@@ -30,12 +32,16 @@ use std::collections::HashMap;
 ///     .args(tuple_args((amount,)))
 ///     .execute(&mut ctx, &[&user])?;
 /// ```
+#[derive(Clone)]
 pub struct InstructionBuilder {
     program_id: Pubkey,
     instruction_name: String,
     accounts: Vec<(String, AccountMeta)>,
     account_indices: HashMap<String, usize>,
-    data: Vec<u8>,
+    remaining_accounts: Vec<AccountMeta>,
+    discriminator: Option<Vec<u8>>,
+    args: Option<Vec<u8>>,
+    idl_instruction: Option<IdlInstruction>,
 }
 
 impl InstructionBuilder {
@@ -51,10 +57,55 @@ impl InstructionBuilder {
             instruction_name: instruction_name.to_string(),
             accounts: Vec::new(),
             account_indices: HashMap::new(),
-            data: Vec::new(),
+            remaining_accounts: Vec::new(),
+            discriminator: None,
+            args: None,
+            idl_instruction: None,
         }
     }
 
+    /// Attach an IDL for automatic account resolution
+    ///
+    /// When the IDL describes this instruction, any account that is not passed
+    /// explicitly is resolved at build time from its constant address or PDA
+    /// seeds, and accounts are emitted in IDL order. Explicit accounts are
+    /// validated against the IDL: `build()` fails with an
+    /// [`IdlError::InvalidAccounts`](crate::IdlError::InvalidAccounts) listing
+    /// missing, extra and out-of-order accounts and writable/signer mismatches.
+    /// If the IDL does not contain the instruction, the builder behaves as if
+    /// no IDL was attached.
+    ///
+    /// The IDL's instruction discriminator is used unless one was set with
+    /// [`Self::discriminator`].
+    ///
+    /// `AnchorContext::instruction_builder` calls this automatically when an
+    /// IDL has been loaded for the program.
+    pub fn with_idl(mut self, idl: &Idl) -> Self {
+        self.idl_instruction = idl::find_instruction(idl, &self.instruction_name).cloned();
+        if self.discriminator.is_none() {
+            self.discriminator = self
+                .idl_instruction
+                .as_ref()
+                .map(|instruction| instruction.discriminator.clone());
+        }
+        self
+    }
+
+    /// Set an explicit instruction discriminator
+    ///
+    /// Needed for instructions declared with `#[instruction(discriminator = ...)]`
+    /// when no IDL is attached. The discriminator may be any length.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// builder.discriminator(&[1]).args(tuple_args((amount,)))
+    /// ```
+    pub fn discriminator(mut self, discriminator: &[u8]) -> Self {
+        self.discriminator = Some(discriminator.to_vec());
+        self
+    }
+
     /// Add a read-only account
     ///
     /// # Arguments
@@ -139,6 +190,22 @@ impl InstructionBuilder {
         self
     }
 
+    /// Add a remaining account
+    ///
+    /// Remaining accounts are appended after the instruction's declared accounts
+    /// and are not checked against the IDL. Use this for accounts the program
+    /// reads through `ctx.remaining_accounts`.
+    pub fn remaining_account(mut self, meta: AccountMeta) -> Self {
+        self.remaining_accounts.push(meta);
+        self
+    }
+
+    /// Add several remaining accounts
+    pub fn remaining_accounts(mut self, metas: Vec<AccountMeta>) -> Self {
+        self.remaining_accounts.extend(metas);
+        self
+    }
+
     /// Add the system program
     ///
     /// Convenience method that adds the system program to the instruction accounts.
@@ -153,6 +220,14 @@ impl InstructionBuilder {
         self.account("token_program", spl_token::id())
     }
 
+    /// Add the Token-2022 program
+    ///
+    /// Convenience method that adds the Token-2022 program as `token_program`,
+    /// for programs that accept any `TokenInterface`.
+    pub fn token_2022_program(self) -> Self {
+        self.account("token_program", spl_token_2022::id())
+    }
+
     /// Add the associated token program
     ///
     /// Convenience method that adds the Associated Token Account program.
@@ -169,7 +244,9 @@ impl InstructionBuilder {
 
     /// Set instruction arguments using AnchorSerialize
     ///
-    /// This method automatically calculates the discriminator and serializes the arguments.
+    /// The arguments are serialized here and prefixed with the discriminator at
+    /// build time: an explicit or IDL-sourced one if set, otherwise
+    /// `sha256("global:<instruction_name>")[..8]`.
     ///
     /// # Arguments
     ///
@@ -183,10 +260,10 @@ impl InstructionBuilder {
     /// builder.args(tuple_args((amount, recipient)))
     /// ```
     pub fn args<T: AnchorSerialize>(mut self, args: T) -> Self {
-        let discriminator = calculate_anchor_discriminator(&self.instruction_name);
-        self.data = discriminator.to_vec();
-        args.serialize(&mut self.data)
+        let mut data = Vec::new();
+        args.serialize(&mut data)
             .expect("Failed to serialize instruction args");
+        self.args = Some(data);
         self
     }
 
@@ -200,21 +277,37 @@ impl InstructionBuilder {
     ///
     /// # Errors
     ///
-    /// Returns an error if `.args()` was not called before building.
+    /// Returns an error if `.args()` was not called before building, or if an
+    /// IDL is attached and the accounts don't match it (see [`Self::with_idl`]).
     pub fn build(self) -> Result<Instruction, Box<dyn std::error::Error>> {
-        if self.data.is_empty() {
-            return Err("No instruction data provided. Call .args() before .build()".into());
-        }
-
-        let accounts: Vec<AccountMeta> = self.accounts
-            .into_iter()
-            .map(|(_, meta)| meta)
-            .collect();
+        let args = self
+            .args
+            .ok_or("No instruction data provided. Call .args() before .build()")?;
+
+        let accounts: Vec<AccountMeta> = match &self.idl_instruction {
+            Some(idl_instruction) => idl::resolve_accounts(
+                &self.program_id,
+                idl_instruction,
+                &self.accounts,
+                &self.remaining_accounts,
+                &args,
+            )?,
+            None => self.accounts
+                .into_iter()
+                .map(|(_, meta)| meta)
+                .chain(self.remaining_accounts)
+                .collect(),
+        };
+
+        let mut data = self
+            .discriminator
+            .unwrap_or_else(|| calculate_anchor_discriminator(&self.instruction_name).to_vec());
+        data.extend_from_slice(&args);
 
         Ok(Instruction {
             program_id: self.program_id,
             accounts,
-            data: self.data,
+            data,
         })
     }
 
@@ -286,14 +379,39 @@ impl InstructionBuilder {
             ctx.svm.latest_blockhash(),
         );
 
-        match ctx.svm.send_transaction(tx) {
-            Ok(result) => Ok(TransactionResult::new(
-                result,
-                Some(instruction_name),
-            )),
-            Err(e) => Err(TransactionError::ExecutionFailed(format!("{:?}", e))),
+        match ctx.send_transaction(tx.clone()) {
+            Ok(result) => Ok(TransactionResult::new(result, &tx, Some(instruction_name))),
+            Err(e) => Err(TransactionError::from(e).with_transaction(&tx)),
         }
     }
+
+    /// Build the instruction and simulate it without changing any state
+    ///
+    /// Useful to check whether an instruction would succeed, or how many
+    /// compute units it needs, before executing it.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let result = ctx.instruction_builder("take")
+    ///     .signer("taker", &taker)
+    ///     // ...
+    ///     .simulate(&ctx, &[&taker])?;
+    /// assert!(result.post_account(&vault).is_some());
+    /// ```
+    pub fn simulate(
+        self,
+        ctx: &crate::AnchorContext,
+        signers: &[&Keypair],
+    ) -> Result<TransactionResult, TransactionError> {
+        let instruction_name = self.instruction_name.clone();
+
+        let instruction = self
+            .build()
+            .map_err(|e| TransactionError::BuildError(e.to_string()))?;
+
+        ctx.simulate_instruction(instruction, signers)
+            .map(|result| result.with_instruction_name(instruction_name))
+    }
 }
 
 /// Wrapper type for tuple arguments to implement AnchorSerialize
@@ -452,4 +570,88 @@ mod tests {
         assert_eq!(accounts[1].pubkey, account1);
         assert_eq!(accounts[2].pubkey, account2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_idl_account_resolution() {
+        let idl = crate::idl::parse_idl(crate::idl::tests::ESCROW_IDL).unwrap();
+        let program_id = crate::idl::idl_program_id(&idl).unwrap();
+        let maker = Keypair::new();
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+
+        let ix = InstructionBuilder::new(&program_id, "make")
+            .with_idl(&idl)
+            .signer("maker", &maker)
+            .account("mint_a", mint_a)
+            .account("mint_b", mint_b)
+            .token_program()
+            .remaining_account(AccountMeta::new_readonly(Pubkey::new_unique(), false))
+            .args(tuple_args((42u64, 500u64, 1000u64)))
+            .build()
+            .unwrap();
+
+        // All nine accounts are present in IDL order, then the remaining account
+        assert_eq!(ix.accounts.len(), 10);
+        assert_eq!(ix.accounts[0].pubkey, maker.pubkey());
+        assert_eq!(ix.accounts[2].pubkey, mint_a);
+        assert_eq!(ix.accounts[3].pubkey, mint_b);
+        assert_eq!(ix.accounts[7].pubkey, spl_token::id());
+    }
+
+    #[test]
+    fn test_idl_validation_error() {
+        let idl = crate::idl::parse_idl(crate::idl::tests::ESCROW_IDL).unwrap();
+        let program_id = crate::idl::idl_program_id(&idl).unwrap();
+        let maker = Keypair::new();
+
+        let err = InstructionBuilder::new(&program_id, "make")
+            .with_idl(&idl)
+            .signer("maker", &maker)
+            .account_mut("mint_b", Pubkey::new_unique())
+            .account("mint_a", Pubkey::new_unique())
+            .token_program()
+            .args(tuple_args((42u64, 500u64, 1000u64)))
+            .build()
+            .unwrap_err();
+
+        let err = err.downcast_ref::<crate::IdlError>().unwrap();
+        let message = err.to_string();
+        assert!(message.contains("account 'mint_b' should be read-only"));
+        assert!(message.contains("account 'mint_b' is passed in IDL position 2 but belongs in position 3"));
+    }
+
+    #[test]
+    fn test_custom_discriminator() {
+        let program_id = Pubkey::new_unique();
+        let user = Keypair::new();
+
+        // Explicit discriminators can be any length and may be set after args
+        let ix = InstructionBuilder::new(&program_id, "test")
+            .signer("user", &user)
+            .args(tuple_args((5u64,)))
+            .discriminator(&[1])
+            .build()
+            .unwrap();
+        assert_eq!(ix.data, vec![1, 5, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_idl_discriminator() {
+        let mut idl = crate::idl::parse_idl(crate::idl::tests::ESCROW_IDL).unwrap();
+        idl.instructions[0].discriminator = vec![9, 9];
+        let program_id = crate::idl::idl_program_id(&idl).unwrap();
+        let maker = Keypair::new();
+
+        let ix = InstructionBuilder::new(&program_id, "make")
+            .with_idl(&idl)
+            .signer("maker", &maker)
+            .account("mint_a", Pubkey::new_unique())
+            .account("mint_b", Pubkey::new_unique())
+            .token_program()
+            .args(tuple_args((42u64, 500u64, 1000u64)))
+            .build()
+            .unwrap();
+        assert_eq!(&ix.data[..2], &[9, 9]);
+        assert_eq!(ix.data.len(), 2 + 24);
+    }
+}
@@ -8,8 +8,23 @@
 //! - **Fluent Instruction Builder**: Build and execute instructions with chainable API
 //! - **Transaction Helpers**: One-line transaction execution with automatic error handling
 //! - **Test Account Helpers**: Create funded accounts, mints, and token accounts in single calls
+//! - **Token-2022 Mints**: Build mints with transfer fees, hooks and other extensions
 //! - **Assertion Helpers**: Clean, readable test assertions for account states
+//! - **Balance Changes**: Assert lamport and token deltas across a transaction
+//! - **Account Snapshots**: Compare decoded account state against checked-in golden files
+//! - **JSON Fixtures**: Dump and load accounts in `solana account --output json` format
+//! - **Time Travel**: Warp slots, epochs and `unix_timestamp` consistently
+//! - **Anchor Error Decoding**: Assert on specific `#[error_code]` and constraint errors
+//! - **Event Parsing**: Typed Anchor events from `emit!` logs and `emit_cpi!` inner instructions
+//! - **Compute Unit Baselines**: Fail tests when instructions get more expensive than a checked-in baseline
+//! - **CPI Call Trees**: See which nested call failed or used the most compute
+//! - **Typed Client Instructions**: Execute `declare_program!` accounts and args structs directly
+//! - **anchor-client Compatibility**: Run `Program::request()` code against LiteSVM (`anchor-client` feature)
+//! - **Local JSON-RPC Server**: Serve LiteSVM to TypeScript tests and wallets without a validator
 //! - **Type-Safe Deserialization**: Automatic Anchor account unpacking with proper types
+//! - **IDL Account Resolution**: Fill in program, PDA and ATA accounts from the Anchor IDL
+//! - **Upgradeable Programs**: Deploy with `ProgramData` and an upgrade authority, then upgrade in place
+//! - **Anchor Workspace Discovery**: Deploy every program and IDL listed in `Anchor.toml`
 //! - **Direct LiteSVM Access**: Full control when you need it
 //!
 //! ## Quick Start
@@ -41,23 +56,60 @@
 //! ```
 
 pub mod account;
+pub mod account_snapshot;
+pub mod anchor_error;
 pub mod assertions;
+pub mod balance_changes;
 pub mod builder;
+pub mod call_tree;
+#[cfg(feature = "anchor-client")]
+pub mod client;
 pub mod context;
+pub mod cu_baseline;
+pub mod events;
+pub mod fixtures;
+pub mod idl;
 pub mod instruction;
 pub mod instruction_builder;
+pub mod mint_builder;
+pub mod program_instruction;
+pub mod rpc;
+pub mod server;
 pub mod test_helpers;
+pub mod time;
 pub mod transaction;
+pub mod upgradeable;
+pub mod workspace;
 
 // Re-export main types for convenience
-pub use account::{get_anchor_account, get_anchor_account_unchecked, AccountError};
+pub use account::{
+    get_anchor_account, get_anchor_account_skip_discriminator, get_anchor_account_unchecked, AccountError,
+};
+pub use account_snapshot::{AccountSnapshot, AccountSnapshotError};
+pub use anchor_error::{AnchorErrorAssertions, AnchorErrorInfo};
 pub use assertions::AssertionHelpers;
+pub use balance_changes::BalanceChanges;
 pub use builder::{AnchorLiteSVM, ProgramTestExt};
-pub use context::AnchorContext;
-pub use instruction::{build_anchor_instruction, calculate_anchor_discriminator};
+pub use call_tree::{CallNode, CallResult, CallTree};
+#[cfg(feature = "anchor-client")]
+pub use client::{LiteSvmClient, LiteSvmSender};
+pub use context::{AnchorContext, Snapshot};
+pub use cu_baseline::{CuBaseline, CuBaselineError};
+pub use fixtures::{AccountFixture, FixtureError};
+pub use idl::{AccountIssue, AccountValidationError, Idl, IdlError};
+pub use instruction::{
+    build_anchor_instruction, build_anchor_instruction_with_discriminator,
+    calculate_anchor_discriminator,
+};
 pub use instruction_builder::{InstructionBuilder, tuple_args, TupleArgs};
+pub use mint_builder::MintBuilder;
+pub use program_instruction::ProgramInstruction;
+pub use server::{RpcServer, RpcServerHandle};
 pub use test_helpers::TestHelpers;
-pub use transaction::{TransactionError, TransactionHelpers, TransactionResult};
+pub use time::TimeHelpers;
+pub use transaction::{ReturnData, TransactionError, TransactionHelpers, TransactionResult};
+pub use upgradeable::{program_data_address, UpgradeError};
+pub use workspace::{AnchorWorkspace, WorkspaceError, WorkspaceProgram};
 
 // Re-export commonly used external types
 pub use litesvm::LiteSVM;
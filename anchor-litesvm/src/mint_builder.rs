@@ -0,0 +1,350 @@
+//! Token-2022 mint builder with extension support
+//!
+//! [`MintBuilder`] creates a Token-2022 mint with any combination of mint
+//! extensions. The account is sized for the chosen extensions and every
+//! extension is initialized before `InitializeMint`, as Token-2022 requires.
+
+use crate::AnchorContext;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use spl_token_2022::extension::{
+    default_account_state, interest_bearing_mint, metadata_pointer, transfer_fee, transfer_hook,
+    ExtensionType,
+};
+use spl_token_2022::state::{AccountState, Mint};
+use std::error::Error;
+
+/// Fluent builder for Token-2022 mints with extensions
+///
+/// Authorities for extensions default to the mint authority, which defaults
+/// to the payer passed to [`MintBuilder::create`].
+///
+/// # Example
+///
+/// ```ignore
+/// use spl_token_2022::state::AccountState;
+///
+/// let mint = ctx.mint_builder()
+///     .decimals(6)
+///     .transfer_fee(100, 1_000_000) // 1%, capped at 1 token
+///     .default_account_state(AccountState::Initialized)
+///     .create(&mut ctx, &authority)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct MintBuilder {
+    decimals: u8,
+    mint_authority: Option<Pubkey>,
+    freeze_authority: Option<Pubkey>,
+    transfer_fee: Option<(u16, u64)>,
+    transfer_hook: Option<Pubkey>,
+    interest_rate: Option<i16>,
+    permanent_delegate: Option<Pubkey>,
+    non_transferable: bool,
+    default_account_state: Option<AccountState>,
+    metadata_pointer: Option<Pubkey>,
+}
+
+impl Default for MintBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MintBuilder {
+    /// Create a builder for a Token-2022 mint with 9 decimals and no extensions
+    pub fn new() -> Self {
+        Self {
+            decimals: 9,
+            mint_authority: None,
+            freeze_authority: None,
+            transfer_fee: None,
+            transfer_hook: None,
+            interest_rate: None,
+            permanent_delegate: None,
+            non_transferable: false,
+            default_account_state: None,
+            metadata_pointer: None,
+        }
+    }
+
+    /// Set the number of decimals
+    pub fn decimals(mut self, decimals: u8) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Set the mint authority (defaults to the payer)
+    pub fn mint_authority(mut self, authority: Pubkey) -> Self {
+        self.mint_authority = Some(authority);
+        self
+    }
+
+    /// Set the freeze authority
+    pub fn freeze_authority(mut self, authority: Pubkey) -> Self {
+        self.freeze_authority = Some(authority);
+        self
+    }
+
+    /// Enable the transfer fee extension
+    ///
+    /// `basis_points` is the fee rate (100 = 1%) and `maximum_fee` caps the
+    /// fee per transfer, in base units.
+    pub fn transfer_fee(mut self, basis_points: u16, maximum_fee: u64) -> Self {
+        self.transfer_fee = Some((basis_points, maximum_fee));
+        self
+    }
+
+    /// Enable the transfer hook extension, calling `program_id` on every transfer
+    pub fn transfer_hook(mut self, program_id: Pubkey) -> Self {
+        self.transfer_hook = Some(program_id);
+        self
+    }
+
+    /// Enable the interest-bearing extension with a rate in basis points
+    pub fn interest_bearing(mut self, rate: i16) -> Self {
+        self.interest_rate = Some(rate);
+        self
+    }
+
+    /// Enable the permanent delegate extension
+    pub fn permanent_delegate(mut self, delegate: Pubkey) -> Self {
+        self.permanent_delegate = Some(delegate);
+        self
+    }
+
+    /// Enable the non-transferable extension
+    pub fn non_transferable(mut self) -> Self {
+        self.non_transferable = true;
+        self
+    }
+
+    /// Enable the default account state extension
+    ///
+    /// Token-2022 requires a freeze authority for this extension, so the mint
+    /// authority is used as freeze authority unless one was set.
+    pub fn default_account_state(mut self, state: AccountState) -> Self {
+        self.default_account_state = Some(state);
+        self
+    }
+
+    /// Enable the metadata pointer extension, pointing at `metadata_address`
+    pub fn metadata_pointer(mut self, metadata_address: Pubkey) -> Self {
+        self.metadata_pointer = Some(metadata_address);
+        self
+    }
+
+    /// The extensions this builder will enable
+    pub fn extension_types(&self) -> Vec<ExtensionType> {
+        let mut types = Vec::new();
+        if self.transfer_fee.is_some() {
+            types.push(ExtensionType::TransferFeeConfig);
+        }
+        if self.transfer_hook.is_some() {
+            types.push(ExtensionType::TransferHook);
+        }
+        if self.interest_rate.is_some() {
+            types.push(ExtensionType::InterestBearingConfig);
+        }
+        if self.permanent_delegate.is_some() {
+            types.push(ExtensionType::PermanentDelegate);
+        }
+        if self.non_transferable {
+            types.push(ExtensionType::NonTransferable);
+        }
+        if self.default_account_state.is_some() {
+            types.push(ExtensionType::DefaultAccountState);
+        }
+        if self.metadata_pointer.is_some() {
+            types.push(ExtensionType::MetadataPointer);
+        }
+        types
+    }
+
+    /// Build the instructions that create and initialize the mint
+    ///
+    /// Returns the `create_account`, extension initialization and
+    /// `initialize_mint` instructions, in that order.
+    pub fn instructions(
+        &self,
+        mint: &Pubkey,
+        payer: &Pubkey,
+        rent_lamports: u64,
+    ) -> Result<Vec<Instruction>, Box<dyn Error>> {
+        let token_program = spl_token_2022::id();
+        let mint_authority = self.mint_authority.unwrap_or(*payer);
+        let freeze_authority = match (self.freeze_authority, &self.default_account_state) {
+            (Some(authority), _) => Some(authority),
+            (None, Some(_)) => Some(mint_authority),
+            (None, None) => None,
+        };
+        let space = ExtensionType::try_calculate_account_len::<Mint>(&self.extension_types())?;
+
+        let mut instructions = vec![solana_sdk::system_instruction::create_account(
+            payer,
+            mint,
+            rent_lamports,
+            space as u64,
+            &token_program,
+        )];
+
+        if let Some((basis_points, maximum_fee)) = self.transfer_fee {
+            instructions.push(transfer_fee::instruction::initialize_transfer_fee_config(
+                &token_program,
+                mint,
+                Some(&mint_authority),
+                Some(&mint_authority),
+                basis_points,
+                maximum_fee,
+            )?);
+        }
+        if let Some(program_id) = self.transfer_hook {
+            instructions.push(transfer_hook::instruction::initialize(
+                &token_program,
+                mint,
+                Some(mint_authority),
+                Some(program_id),
+            )?);
+        }
+        if let Some(rate) = self.interest_rate {
+            instructions.push(interest_bearing_mint::instruction::initialize(
+                &token_program,
+                mint,
+                Some(mint_authority),
+                rate,
+            )?);
+        }
+        if let Some(delegate) = &self.permanent_delegate {
+            instructions.push(spl_token_2022::instruction::initialize_permanent_delegate(
+                &token_program,
+                mint,
+                delegate,
+            )?);
+        }
+        if self.non_transferable {
+            instructions.push(spl_token_2022::instruction::initialize_non_transferable_mint(
+                &token_program,
+                mint,
+            )?);
+        }
+        if let Some(state) = &self.default_account_state {
+            instructions.push(
+                default_account_state::instruction::initialize_default_account_state(
+                    &token_program,
+                    mint,
+                    state,
+                )?,
+            );
+        }
+        if let Some(metadata_address) = self.metadata_pointer {
+            instructions.push(metadata_pointer::instruction::initialize(
+                &token_program,
+                mint,
+                Some(mint_authority),
+                Some(metadata_address),
+            )?);
+        }
+
+        instructions.push(spl_token_2022::instruction::initialize_mint(
+            &token_program,
+            mint,
+            &mint_authority,
+            freeze_authority.as_ref(),
+            self.decimals,
+        )?);
+
+        Ok(instructions)
+    }
+
+    /// Create the mint, paid for and signed by `payer`
+    ///
+    /// Returns the new mint's keypair.
+    pub fn create(
+        self,
+        ctx: &mut AnchorContext,
+        payer: &Keypair,
+    ) -> Result<Keypair, Box<dyn Error>> {
+        let mint = Keypair::new();
+        let space = ExtensionType::try_calculate_account_len::<Mint>(&self.extension_types())?;
+        let rent = ctx.svm.minimum_balance_for_rent_exemption(space);
+        let instructions = self.instructions(&mint.pubkey(), &payer.pubkey(), rent)?;
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[payer, &mint],
+            ctx.svm.latest_blockhash(),
+        );
+
+        ctx.send_transaction(tx).map_err(|e| format!("Transaction failed: {:?}", e))?;
+        Ok(mint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::token::get_mint;
+    use crate::TestHelpers;
+    use litesvm::LiteSVM;
+    use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+    use spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+
+    #[test]
+    fn test_mint_with_extensions() {
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let authority = ctx.create_funded_account(10_000_000_000).unwrap();
+
+        let mint = MintBuilder::new()
+            .decimals(6)
+            .transfer_fee(100, 10_000)
+            .interest_bearing(500)
+            .permanent_delegate(authority.pubkey())
+            .default_account_state(AccountState::Initialized)
+            .metadata_pointer(Pubkey::new_unique())
+            .create(&mut ctx, &authority)
+            .unwrap();
+
+        let account = ctx.svm.get_account(&mint.pubkey()).unwrap();
+        assert_eq!(account.owner, spl_token_2022::id());
+
+        let state = StateWithExtensions::<Mint>::unpack(&account.data).unwrap();
+        assert_eq!(state.base.decimals, 6);
+        assert_eq!(state.base.freeze_authority, Some(authority.pubkey()).into());
+        assert_eq!(
+            state.get_extension_types().unwrap(),
+            vec![
+                ExtensionType::TransferFeeConfig,
+                ExtensionType::InterestBearingConfig,
+                ExtensionType::PermanentDelegate,
+                ExtensionType::DefaultAccountState,
+                ExtensionType::MetadataPointer,
+            ]
+        );
+
+        let fee_config = state.get_extension::<TransferFeeConfig>().unwrap();
+        assert_eq!(u16::from(fee_config.newer_transfer_fee.transfer_fee_basis_points), 100);
+        assert_eq!(u64::from(fee_config.newer_transfer_fee.maximum_fee), 10_000);
+    }
+
+    #[test]
+    fn test_token_account_for_extension_mint() {
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let authority = ctx.create_funded_account(10_000_000_000).unwrap();
+        let owner = ctx.create_funded_account(1_000_000_000).unwrap();
+
+        let mint = ctx
+            .mint_builder()
+            .transfer_fee(50, u64::MAX)
+            .non_transferable()
+            .create(&mut ctx, &authority)
+            .unwrap();
+        assert_eq!(get_mint(&ctx, &mint.pubkey()).unwrap().decimals, 9);
+
+        let ata = ctx
+            .create_token_account(&owner, &mint.pubkey(), Some((1_000, &authority)))
+            .unwrap();
+        assert_eq!(crate::test_helpers::token::get_token_balance(&ctx, &ata).unwrap(), 1_000);
+    }
+}
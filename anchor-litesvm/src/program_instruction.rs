@@ -0,0 +1,187 @@
+//! Typed instructions from Anchor client structs
+//!
+//! Programs built with Anchor expose typed `accounts` and `args` structs, either
+//! through the program crate's `cpi`/`client` feature or through
+//! `declare_program!`. [`ProgramInstruction`] turns a pair of those into an
+//! instruction that is executed against LiteSVM with the usual
+//! [`TransactionResult`] and [`TransactionError`] helpers.
+
+use crate::transaction::{TransactionError, TransactionHelpers, TransactionResult};
+use crate::AnchorContext;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+
+/// An instruction built from typed Anchor `accounts` and `args` structs
+///
+/// # Example
+///
+/// ```ignore
+/// anchor_lang::declare_program!(anchor_escrow);
+/// use anchor_escrow::client::{accounts, args};
+///
+/// ctx.program_ix(
+///     accounts::Make {
+///         maker: maker.pubkey(),
+///         escrow: escrow_pda,
+///         // ...
+///     },
+///     args::Make { seed, receive: 500_000_000, amount: 1_000_000_000 },
+/// )
+/// .execute(&mut ctx, &[&maker])?
+/// .assert_success();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProgramInstruction {
+    instruction: Instruction,
+    instruction_name: String,
+}
+
+impl ProgramInstruction {
+    /// Build an instruction for `program_id` from typed accounts and args
+    ///
+    /// The discriminator comes from the args type, so custom discriminators
+    /// work without any extra configuration.
+    pub fn new<A, D>(program_id: &Pubkey, accounts: A, args: D) -> Self
+    where
+        A: ToAccountMetas,
+        D: InstructionData,
+    {
+        Self {
+            instruction: Instruction {
+                program_id: *program_id,
+                accounts: accounts.to_account_metas(None),
+                data: args.data(),
+            },
+            instruction_name: instruction_name::<D>(),
+        }
+    }
+
+    /// Append an account after the typed accounts (`ctx.remaining_accounts`)
+    pub fn remaining_account(mut self, meta: AccountMeta) -> Self {
+        self.instruction.accounts.push(meta);
+        self
+    }
+
+    /// Append several accounts after the typed accounts
+    pub fn remaining_accounts(mut self, metas: Vec<AccountMeta>) -> Self {
+        self.instruction.accounts.extend(metas);
+        self
+    }
+
+    /// The instruction name, derived from the args type (`args::Make` -> `make`)
+    pub fn name(&self) -> &str {
+        &self.instruction_name
+    }
+
+    /// Get the built instruction
+    pub fn instruction(&self) -> &Instruction {
+        &self.instruction
+    }
+
+    /// Consume the builder and return the instruction
+    pub fn build(self) -> Instruction {
+        self.instruction
+    }
+
+    /// Execute the instruction in its own transaction
+    ///
+    /// The first signer pays for the transaction.
+    pub fn execute(
+        self,
+        ctx: &mut AnchorContext,
+        signers: &[&Keypair],
+    ) -> Result<TransactionResult, TransactionError> {
+        ctx.send_instruction(self.instruction, signers)
+            .map(|result| result.with_instruction_name(self.instruction_name))
+    }
+
+    /// Simulate the instruction without changing any state
+    pub fn simulate(
+        self,
+        ctx: &AnchorContext,
+        signers: &[&Keypair],
+    ) -> Result<TransactionResult, TransactionError> {
+        ctx.simulate_instruction(self.instruction, signers)
+            .map(|result| result.with_instruction_name(self.instruction_name))
+    }
+}
+
+/// Snake-case name of an args type, e.g. `client::args::MakeOffer` -> `make_offer`
+fn instruction_name<D>() -> String {
+    let type_name = std::any::type_name::<D>();
+    to_snake_case(type_name.rsplit("::").next().unwrap_or(type_name))
+}
+
+/// Convert a PascalCase name to snake_case, e.g. `TransferChecked` -> `transfer_checked`
+pub(crate) fn to_snake_case(pascal: &str) -> String {
+    let mut name = String::with_capacity(pascal.len() + 4);
+    for (i, c) in pascal.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                name.push('_');
+            }
+            name.extend(c.to_lowercase());
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::*;
+    use anchor_lang::Discriminator;
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    struct MakeOffer {
+        seed: u64,
+    }
+
+    impl Discriminator for MakeOffer {
+        const DISCRIMINATOR: &'static [u8] = &[3];
+    }
+
+    impl InstructionData for MakeOffer {}
+
+    struct MakeOfferAccounts {
+        maker: Pubkey,
+        offer: Pubkey,
+    }
+
+    impl ToAccountMetas for MakeOfferAccounts {
+        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+            vec![
+                AccountMeta::new(self.maker, true),
+                AccountMeta::new(self.offer, false),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_program_instruction() {
+        let program_id = Pubkey::new_unique();
+        let maker = Pubkey::new_unique();
+        let offer = Pubkey::new_unique();
+        let extra = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+
+        let ix = ProgramInstruction::new(
+            &program_id,
+            MakeOfferAccounts { maker, offer },
+            MakeOffer { seed: 7 },
+        )
+        .remaining_account(extra.clone());
+
+        assert_eq!(ix.name(), "make_offer");
+        let ix = ix.build();
+        assert_eq!(ix.program_id, program_id);
+        assert_eq!(
+            ix.accounts,
+            vec![AccountMeta::new(maker, true), AccountMeta::new(offer, false), extra]
+        );
+        assert_eq!(ix.data, vec![3, 7, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,660 @@
+//! Solana JSON-RPC request handling backed by an [`AnchorContext`]
+//!
+//! [`handle_request`] answers a subset of the Solana JSON-RPC API directly
+//! from the in-process LiteSVM, using the same JSON shapes as a validator so
+//! that `RpcClient` and anchor-client can parse the responses. Transactions
+//! execute immediately and are reported as finalized.
+
+use crate::AnchorContext;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_program::address_lookup_table::state::AddressLookupTable;
+use solana_program::clock::Clock;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use std::fmt;
+use std::str::FromStr;
+
+/// JSON-RPC "method not found" error code
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC "invalid params" error code
+pub const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC "internal error" error code
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Solana's "transaction simulation failed" error code for `sendTransaction`
+pub const SEND_TRANSACTION_PREFLIGHT_FAILURE: i64 = -32002;
+
+/// Version reported by `getVersion`
+const SOLANA_CORE_VERSION: &str = "2.2.0";
+
+/// Number of slots a blockhash stays valid on a real cluster
+const MAX_PROCESSING_AGE: u64 = 150;
+
+/// A JSON-RPC error object (`{"code", "message", "data"}`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcErrorObject {
+    /// JSON-RPC error code
+    pub code: i64,
+    /// Human-readable message
+    pub message: String,
+    /// Optional structured data, e.g. the simulation result of a failed send
+    pub data: Option<Value>,
+}
+
+impl RpcErrorObject {
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Render as a JSON-RPC error object
+    pub fn to_json(&self) -> Value {
+        let mut error = json!({ "code": self.code, "message": self.message });
+        if let Some(data) = &self.data {
+            error["data"] = data.clone();
+        }
+        error
+    }
+}
+
+impl fmt::Display for RpcErrorObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RPC error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for RpcErrorObject {}
+
+/// Handle one JSON-RPC request against the context's LiteSVM
+///
+/// `params` is the request's `params` array. Returns the `result` value, or
+/// the error object to send back.
+///
+/// Supported methods: `getVersion`, `getHealth`, `getSlot`, `getBlockHeight`,
+/// `getLatestBlockhash`, `isBlockhashValid`, `getBalance`, `getAccountInfo`,
+/// `getMultipleAccounts`, `getProgramAccounts`,
+/// `getMinimumBalanceForRentExemption`, `requestAirdrop`, `sendTransaction`,
+/// `simulateTransaction` and `getSignatureStatuses`.
+///
+/// Addresses the request refers to are added to the context's tracked
+/// accounts, which `getProgramAccounts` searches.
+pub fn handle_request(
+    ctx: &mut AnchorContext,
+    method: &str,
+    params: &Value,
+) -> Result<Value, RpcErrorObject> {
+    track_request(ctx, method, params);
+
+    match method {
+        "getVersion" => Ok(json!({ "solana-core": SOLANA_CORE_VERSION, "feature-set": null })),
+        "getHealth" => Ok(json!("ok")),
+        "getSlot" | "getBlockHeight" => Ok(json!(current_slot(ctx))),
+        "getLatestBlockhash" => Ok(with_context(
+            ctx,
+            json!({
+                "blockhash": ctx.svm.latest_blockhash().to_string(),
+                "lastValidBlockHeight": current_slot(ctx) + MAX_PROCESSING_AGE,
+            }),
+        )),
+        "isBlockhashValid" => {
+            let blockhash = param_str(params, 0)?;
+            let valid = ctx.svm.latest_blockhash().to_string() == blockhash;
+            Ok(with_context(ctx, json!(valid)))
+        }
+        "getBalance" => {
+            let pubkey = param_pubkey(params, 0)?;
+            let lamports = ctx.svm.get_balance(&pubkey).unwrap_or(0);
+            Ok(with_context(ctx, json!(lamports)))
+        }
+        "getAccountInfo" => {
+            let pubkey = param_pubkey(params, 0)?;
+            let account = get_live_account(ctx, &pubkey).map(|account| account_json(&account));
+            Ok(with_context(ctx, json!(account)))
+        }
+        "getMultipleAccounts" => {
+            let accounts = param_pubkeys(params, 0)?
+                .iter()
+                .map(|pubkey| get_live_account(ctx, pubkey).map(|account| account_json(&account)))
+                .collect::<Vec<_>>();
+            Ok(with_context(ctx, json!(accounts)))
+        }
+        "getMinimumBalanceForRentExemption" => {
+            let data_len = params
+                .get(0)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| RpcErrorObject::invalid_params("Expected data length at index 0"))?;
+            Ok(json!(ctx.svm.minimum_balance_for_rent_exemption(data_len as usize)))
+        }
+        "requestAirdrop" => {
+            let pubkey = param_pubkey(params, 0)?;
+            let lamports = params
+                .get(1)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| RpcErrorObject::invalid_params("Expected lamports at index 1"))?;
+            ctx.svm
+                .airdrop(&pubkey, lamports)
+                .map(|meta| json!(meta.signature.to_string()))
+                .map_err(|failed| RpcErrorObject {
+                    code: INTERNAL_ERROR,
+                    message: format!("Airdrop failed: {}", failed.err),
+                    data: None,
+                })
+        }
+        "getProgramAccounts" => program_accounts(ctx, params),
+        "sendTransaction" => send_transaction(ctx, params),
+        "simulateTransaction" => simulate_transaction(ctx, params),
+        "getSignatureStatuses" => signature_statuses(ctx, params),
+        _ => Err(RpcErrorObject {
+            code: METHOD_NOT_FOUND,
+            message: format!("Method not found: {}", method),
+            data: None,
+        }),
+    }
+}
+
+/// Remember the addresses a request refers to
+fn track_request(ctx: &mut AnchorContext, method: &str, params: &Value) {
+    let pubkeys = match method {
+        "sendTransaction" | "simulateTransaction" => decode_transaction(params)
+            .map(|tx| transaction_account_keys(ctx, &tx))
+            .unwrap_or_default(),
+        "getAccountInfo" | "getBalance" | "requestAirdrop" => {
+            param_pubkey(params, 0).map(|pubkey| vec![pubkey]).unwrap_or_default()
+        }
+        "getMultipleAccounts" => param_pubkeys(params, 0).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    ctx.track_accounts(pubkeys);
+}
+
+fn program_accounts(ctx: &AnchorContext, params: &Value) -> Result<Value, RpcErrorObject> {
+    let program_id = param_pubkey(params, 0)?;
+    let config = params.get(1);
+    let filters = config
+        .and_then(|config| config.get("filters"))
+        .and_then(Value::as_array)
+        .map(|filters| {
+            filters
+                .iter()
+                .map(AccountFilter::parse)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let accounts = ctx
+        .known_accounts()
+        .filter_map(|pubkey| get_live_account(ctx, pubkey).map(|account| (pubkey, account)))
+        .filter(|(_, account)| account.owner == program_id)
+        .filter(|(_, account)| filters.iter().all(|filter| filter.matches(&account.data)))
+        .map(|(pubkey, account)| {
+            json!({ "pubkey": pubkey.to_string(), "account": account_json(&account) })
+        })
+        .collect::<Vec<_>>();
+
+    let with_context_requested = config
+        .and_then(|config| config.get("withContext"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if with_context_requested {
+        Ok(with_context(ctx, json!(accounts)))
+    } else {
+        Ok(json!(accounts))
+    }
+}
+
+fn send_transaction(ctx: &mut AnchorContext, params: &Value) -> Result<Value, RpcErrorObject> {
+    let tx = decode_transaction(params)?;
+
+    match ctx.svm.send_transaction(tx) {
+        Ok(meta) => Ok(json!(meta.signature.to_string())),
+        Err(failed) => Err(RpcErrorObject {
+            code: SEND_TRANSACTION_PREFLIGHT_FAILURE,
+            message: format!("Transaction simulation failed: {}", failed.err),
+            data: Some(json!({
+                "err": failed.err,
+                "logs": failed.meta.logs,
+                "accounts": null,
+                "unitsConsumed": failed.meta.compute_units_consumed,
+                "returnData": null,
+                "innerInstructions": null,
+            })),
+        }),
+    }
+}
+
+fn simulate_transaction(ctx: &mut AnchorContext, params: &Value) -> Result<Value, RpcErrorObject> {
+    let tx = decode_transaction(params)?;
+
+    let (err, meta) = match ctx.svm.simulate_transaction(tx) {
+        Ok(info) => (None, info.meta),
+        Err(failed) => (Some(failed.err), failed.meta),
+    };
+    let return_data = (!meta.return_data.data.is_empty()).then(|| {
+        json!({
+            "programId": meta.return_data.program_id.to_string(),
+            "data": [STANDARD.encode(&meta.return_data.data), "base64"],
+        })
+    });
+
+    Ok(with_context(
+        ctx,
+        json!({
+            "err": err,
+            "logs": meta.logs,
+            "accounts": null,
+            "unitsConsumed": meta.compute_units_consumed,
+            "returnData": return_data,
+            "innerInstructions": null,
+        }),
+    ))
+}
+
+fn signature_statuses(ctx: &AnchorContext, params: &Value) -> Result<Value, RpcErrorObject> {
+    let signatures = params
+        .get(0)
+        .and_then(Value::as_array)
+        .ok_or_else(|| RpcErrorObject::invalid_params("Expected signature array at index 0"))?;
+    let slot = current_slot(ctx);
+
+    let statuses = signatures
+        .iter()
+        .map(|signature| {
+            let signature = signature
+                .as_str()
+                .and_then(|s| Signature::from_str(s).ok())
+                .ok_or_else(|| RpcErrorObject::invalid_params("Invalid signature"))?;
+
+            Ok(ctx.svm.get_transaction(&signature).map(|result| {
+                let err = result.as_ref().err().map(|failed| &failed.err);
+                json!({
+                    "slot": slot,
+                    "confirmations": null,
+                    "err": err,
+                    "status": match err {
+                        None => json!({ "Ok": null }),
+                        Some(err) => json!({ "Err": err }),
+                    },
+                    "confirmationStatus": "finalized",
+                })
+            }))
+        })
+        .collect::<Result<Vec<_>, RpcErrorObject>>()?;
+
+    Ok(with_context(ctx, json!(statuses)))
+}
+
+/// Decode a wire transaction in the encoding named by the request config
+pub(crate) fn decode_transaction(params: &Value) -> Result<VersionedTransaction, RpcErrorObject> {
+    let encoded = param_str(params, 0)?;
+    let encoding = params
+        .get(1)
+        .and_then(|config| config.get("encoding"))
+        .and_then(Value::as_str)
+        .unwrap_or("base58");
+
+    let bytes = match encoding {
+        "base58" => bs58::decode(encoded).into_vec().map_err(|e| e.to_string()),
+        "base64" => STANDARD.decode(encoded).map_err(|e| e.to_string()),
+        other => Err(format!("unsupported encoding: {}", other)),
+    }
+    .map_err(|e| RpcErrorObject::invalid_params(format!("Invalid transaction: {}", e)))?;
+
+    bincode::deserialize(&bytes)
+        .map_err(|e| RpcErrorObject::invalid_params(format!("Invalid transaction: {}", e)))
+}
+
+/// All account keys a transaction refers to, including those loaded from address lookup tables
+///
+/// Lookup tables are read from the current state; tables that are missing or
+/// cannot be parsed contribute only their own address.
+fn transaction_account_keys(ctx: &AnchorContext, tx: &VersionedTransaction) -> Vec<Pubkey> {
+    let mut keys = tx.message.static_account_keys().to_vec();
+    for lookup in tx.message.address_table_lookups().unwrap_or_default() {
+        keys.push(lookup.account_key);
+        let Some(table) = ctx.svm.get_account(&lookup.account_key) else {
+            continue;
+        };
+        let Ok(table) = AddressLookupTable::deserialize(&table.data) else {
+            continue;
+        };
+        keys.extend(
+            lookup
+                .writable_indexes
+                .iter()
+                .chain(&lookup.readonly_indexes)
+                .filter_map(|index| table.addresses.get(usize::from(*index)).copied()),
+        );
+    }
+    keys
+}
+
+/// Get an account, treating closed (zero-lamport) accounts as missing like a validator
+pub(crate) fn get_live_account(ctx: &AnchorContext, pubkey: &Pubkey) -> Option<Account> {
+    ctx.svm
+        .get_account(pubkey)
+        .filter(|account| account.lamports > 0)
+}
+
+/// Render an account as a base64-encoded `UiAccount`
+pub(crate) fn account_json(account: &Account) -> Value {
+    json!({
+        "lamports": account.lamports,
+        "data": [STANDARD.encode(&account.data), "base64"],
+        "owner": account.owner.to_string(),
+        "executable": account.executable,
+        "rentEpoch": account.rent_epoch,
+        "space": account.data.len(),
+    })
+}
+
+/// Wrap a value in an `RpcResponse` with the current slot as context
+pub(crate) fn with_context(ctx: &AnchorContext, value: Value) -> Value {
+    json!({
+        "context": { "slot": current_slot(ctx), "apiVersion": SOLANA_CORE_VERSION },
+        "value": value,
+    })
+}
+
+fn current_slot(ctx: &AnchorContext) -> u64 {
+    ctx.svm.get_sysvar::<Clock>().slot
+}
+
+pub(crate) fn param_str(params: &Value, index: usize) -> Result<&str, RpcErrorObject> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcErrorObject::invalid_params(format!("Expected string at index {}", index)))
+}
+
+pub(crate) fn param_pubkey(params: &Value, index: usize) -> Result<Pubkey, RpcErrorObject> {
+    let value = param_str(params, index)?;
+    Pubkey::from_str(value)
+        .map_err(|_| RpcErrorObject::invalid_params(format!("Invalid pubkey: {}", value)))
+}
+
+pub(crate) fn param_pubkeys(params: &Value, index: usize) -> Result<Vec<Pubkey>, RpcErrorObject> {
+    params
+        .get(index)
+        .and_then(Value::as_array)
+        .ok_or_else(|| RpcErrorObject::invalid_params(format!("Expected array at index {}", index)))?
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .and_then(|s| Pubkey::from_str(s).ok())
+                .ok_or_else(|| RpcErrorObject::invalid_params(format!("Invalid pubkey: {}", value)))
+        })
+        .collect()
+}
+
+/// A `getProgramAccounts` filter
+enum AccountFilter {
+    DataSize(usize),
+    Memcmp { offset: usize, bytes: Vec<u8> },
+}
+
+impl AccountFilter {
+    fn parse(filter: &Value) -> Result<Self, RpcErrorObject> {
+        let invalid = || RpcErrorObject {
+            code: INVALID_PARAMS,
+            message: format!("Invalid filter: {}", filter),
+            data: None,
+        };
+
+        if let Some(size) = filter.get("dataSize").and_then(Value::as_u64) {
+            return Ok(Self::DataSize(size as usize));
+        }
+
+        let memcmp = filter.get("memcmp").ok_or_else(invalid)?;
+        let offset = memcmp.get("offset").and_then(Value::as_u64).ok_or_else(invalid)?;
+        let encoded = memcmp.get("bytes").and_then(Value::as_str).ok_or_else(invalid)?;
+        let bytes = match memcmp.get("encoding").and_then(Value::as_str) {
+            Some("base64") => STANDARD.decode(encoded).ok(),
+            Some("base58") | None => bs58::decode(encoded).into_vec().ok(),
+            Some(_) => None,
+        }
+        .ok_or_else(invalid)?;
+
+        Ok(Self::Memcmp {
+            offset: offset as usize,
+            bytes,
+        })
+    }
+
+    fn matches(&self, data: &[u8]) -> bool {
+        match self {
+            Self::DataSize(size) => data.len() == *size,
+            Self::Memcmp { offset, bytes } => data
+                .get(*offset..)
+                .is_some_and(|data| data.starts_with(bytes)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use litesvm::LiteSVM;
+    use solana_sdk::signature::{Keypair, Signer};
+    use solana_sdk::system_instruction;
+    use solana_sdk::transaction::Transaction;
+
+    fn context() -> AnchorContext {
+        AnchorContext::new(LiteSVM::new(), Pubkey::new_unique())
+    }
+
+    #[test]
+    fn test_account_queries() {
+        let mut ctx = context();
+        let user = Pubkey::new_unique();
+        ctx.svm.airdrop(&user, 1_000_000).unwrap();
+
+        let balance = handle_request(&mut ctx, "getBalance", &json!([user.to_string()])).unwrap();
+        assert_eq!(balance["value"], 1_000_000);
+
+        let info = handle_request(&mut ctx, "getAccountInfo", &json!([user.to_string()])).unwrap();
+        assert_eq!(info["value"]["lamports"], 1_000_000);
+        assert_eq!(info["value"]["owner"], solana_program::system_program::id().to_string());
+
+        let missing = Pubkey::new_unique().to_string();
+        let info = handle_request(&mut ctx, "getAccountInfo", &json!([missing])).unwrap();
+        assert!(info["value"].is_null());
+    }
+
+    #[test]
+    fn test_send_transaction_and_status() {
+        let mut ctx = context();
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        ctx.svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+
+        let tx = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer.pubkey(), &recipient, 5_000_000)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            ctx.svm.latest_blockhash(),
+        );
+        let encoded = STANDARD.encode(bincode::serialize(&tx).unwrap());
+
+        let signature = handle_request(
+            &mut ctx,
+            "sendTransaction",
+            &json!([encoded, { "encoding": "base64" }]),
+        )
+        .unwrap();
+        assert_eq!(signature, tx.signatures[0].to_string());
+        assert_eq!(ctx.svm.get_balance(&recipient), Some(5_000_000));
+
+        let statuses =
+            handle_request(&mut ctx, "getSignatureStatuses", &json!([[signature]])).unwrap();
+        assert_eq!(statuses["value"][0]["status"], json!({ "Ok": null }));
+        assert_eq!(statuses["value"][0]["confirmationStatus"], "finalized");
+    }
+
+    #[test]
+    fn test_airdrop_and_multiple_accounts() {
+        let mut ctx = context();
+        let user = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+
+        let signature =
+            handle_request(&mut ctx, "requestAirdrop", &json!([user.to_string(), 42_000])).unwrap();
+        assert!(signature.is_string());
+
+        let accounts = handle_request(
+            &mut ctx,
+            "getMultipleAccounts",
+            &json!([[user.to_string(), missing.to_string()]]),
+        )
+        .unwrap();
+        assert_eq!(accounts["value"][0]["lamports"], 42_000);
+        assert!(accounts["value"][1].is_null());
+    }
+
+    #[test]
+    fn test_simulate_transaction_does_not_commit() {
+        let mut ctx = context();
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        ctx.svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+
+        let tx = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer.pubkey(), &recipient, 5_000_000)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            ctx.svm.latest_blockhash(),
+        );
+        let encoded = STANDARD.encode(bincode::serialize(&tx).unwrap());
+
+        let result = handle_request(
+            &mut ctx,
+            "simulateTransaction",
+            &json!([encoded, { "encoding": "base64" }]),
+        )
+        .unwrap();
+        assert!(result["value"]["err"].is_null());
+        assert!(result["value"]["unitsConsumed"].as_u64().unwrap() > 0);
+        assert_eq!(ctx.svm.get_balance(&recipient), None);
+    }
+
+    #[test]
+    fn test_send_transaction_failure() {
+        let mut ctx = context();
+        let payer = Keypair::new();
+        ctx.svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+
+        // Transfer more than the payer has
+        let tx = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 2_000_000_000)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            ctx.svm.latest_blockhash(),
+        );
+        let encoded = bs58::encode(bincode::serialize(&tx).unwrap()).into_string();
+
+        let err = handle_request(&mut ctx, "sendTransaction", &json!([encoded])).unwrap_err();
+        assert_eq!(err.code, SEND_TRANSACTION_PREFLIGHT_FAILURE);
+        let data = err.data.unwrap();
+        assert!(data["err"]["InstructionError"].is_array());
+        assert!(data["logs"].as_array().is_some_and(|logs| !logs.is_empty()));
+    }
+
+    #[test]
+    fn test_unknown_method() {
+        let err = handle_request(&mut context(), "getBlock", &json!([])).unwrap_err();
+        assert_eq!(err.code, METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_program_accounts_filters() {
+        let mut ctx = context();
+        let program_id = Pubkey::new_unique();
+        let matching = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        for (pubkey, data) in [(matching, vec![1, 2, 3, 4]), (other, vec![1, 9, 3, 4])] {
+            let account = Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            };
+            ctx.svm.set_account(pubkey, account).unwrap();
+        }
+        ctx.track_accounts([matching, other]);
+
+        let params = json!([
+            program_id.to_string(),
+            { "filters": [
+                { "dataSize": 4 },
+                { "memcmp": { "offset": 1, "bytes": bs58::encode([2, 3]).into_string() } },
+            ] },
+        ]);
+        let accounts = handle_request(&mut ctx, "getProgramAccounts", &params).unwrap();
+
+        assert_eq!(accounts.as_array().unwrap().len(), 1);
+        assert_eq!(accounts[0]["pubkey"], matching.to_string());
+    }
+
+    #[test]
+    fn test_tracks_lookup_table_addresses() {
+        use solana_program::address_lookup_table::state::LookupTableMeta;
+        use solana_sdk::message::v0::{Message, MessageAddressTableLookup};
+        use solana_sdk::message::{MessageHeader, VersionedMessage};
+        use std::borrow::Cow;
+
+        let mut ctx = context();
+        let table_key = Pubkey::new_unique();
+        let looked_up = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let table = AddressLookupTable {
+            meta: LookupTableMeta::default(),
+            addresses: Cow::Borrowed(&looked_up),
+        };
+        let account = Account {
+            lamports: 1_000_000,
+            data: table.serialize_for_tests().unwrap(),
+            owner: solana_program::address_lookup_table::program::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        ctx.svm.set_account(table_key, account).unwrap();
+
+        let payer = Pubkey::new_unique();
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 0,
+                },
+                account_keys: vec![payer],
+                recent_blockhash: Default::default(),
+                instructions: vec![],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: table_key,
+                    writable_indexes: vec![2],
+                    readonly_indexes: vec![0],
+                }],
+            }),
+        };
+        let params = json!([bs58::encode(bincode::serialize(&tx).unwrap()).into_string()]);
+        handle_request(&mut ctx, "simulateTransaction", &params).unwrap();
+
+        let known: Vec<&Pubkey> = ctx.known_accounts().collect();
+        assert!(known.contains(&&payer));
+        assert!(known.contains(&&table_key));
+        assert!(known.contains(&&looked_up[0]));
+        assert!(known.contains(&&looked_up[2]));
+        assert!(!known.contains(&&looked_up[1]));
+    }
+}
@@ -0,0 +1,340 @@
+//! Local JSON-RPC server over an [`AnchorContext`]
+//!
+//! [`RpcServer`] exposes the context's LiteSVM on a local HTTP port as a
+//! subset of the Solana JSON-RPC API, so TypeScript Anchor tests, wallets and
+//! other RPC clients can use the in-process VM instead of
+//! `solana-test-validator`. Requests are answered by
+//! [`crate::rpc::handle_request`]; websocket subscriptions are not supported.
+
+use crate::rpc::{handle_request, RpcErrorObject};
+use crate::AnchorContext;
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// JSON-RPC "parse error" error code
+const PARSE_ERROR: i64 = -32700;
+
+/// How long a connection may stall before it is dropped
+///
+/// Connections are handled one at a time, so without a timeout a client that
+/// connects and never sends a request would block every other client.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// JSON-RPC server answering requests from a shared [`AnchorContext`]
+///
+/// LiteSVM cannot list its accounts, so `getProgramAccounts` searches the
+/// accounts the context has seen (see [`AnchorContext::track_accounts`]):
+/// accounts created through its helpers, every account key of transactions
+/// sent or simulated, including addresses loaded from lookup tables that
+/// exist when the transaction arrives, and every queried or airdropped
+/// address. Register accounts written directly with `svm.set_account` if
+/// clients need to find them that way.
+///
+/// # Example
+///
+/// ```ignore
+/// use anchor_litesvm::{AnchorLiteSVM, RpcServer};
+///
+/// let ctx = AnchorLiteSVM::build_with_program(program_id, program_bytes);
+/// let server = RpcServer::new(ctx).spawn("127.0.0.1:8899")?;
+///
+/// // ANCHOR_PROVIDER_URL=http://127.0.0.1:8899 yarn run ts-mocha ...
+/// println!("Listening on {}", server.url());
+/// server.join();
+/// ```
+#[derive(Clone)]
+pub struct RpcServer {
+    ctx: Arc<Mutex<AnchorContext>>,
+}
+
+impl RpcServer {
+    /// Create a server for a context
+    pub fn new(ctx: AnchorContext) -> Self {
+        Self::from_shared(Arc::new(Mutex::new(ctx)))
+    }
+
+    /// Create a server for a context that is shared with other users,
+    /// such as a [`LiteSvmClient`](crate::LiteSvmClient)
+    pub fn from_shared(ctx: Arc<Mutex<AnchorContext>>) -> Self {
+        Self { ctx }
+    }
+
+    /// Lock the shared context for direct access
+    pub fn context(&self) -> MutexGuard<'_, AnchorContext> {
+        self.ctx.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Make accounts discoverable through `getProgramAccounts`
+    ///
+    /// Shorthand for [`AnchorContext::track_accounts`] on the shared context.
+    pub fn track_accounts(&self, pubkeys: impl IntoIterator<Item = Pubkey>) {
+        self.context().track_accounts(pubkeys);
+    }
+
+    /// Handle a JSON-RPC request body, single or batch, and return the response body
+    pub fn handle(&self, body: &str) -> String {
+        let response = match serde_json::from_str::<Value>(body) {
+            Ok(Value::Array(requests)) => {
+                Value::Array(requests.iter().map(|request| self.handle_one(request)).collect())
+            }
+            Ok(request) => self.handle_one(&request),
+            Err(e) => json!({
+                "jsonrpc": "2.0",
+                "error": { "code": PARSE_ERROR, "message": format!("Parse error: {}", e) },
+                "id": null,
+            }),
+        };
+        response.to_string()
+    }
+
+    /// Serve requests on `listener` until the process exits
+    pub fn serve(self, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            self.handle_connection(stream?);
+        }
+        Ok(())
+    }
+
+    /// Bind to `addr` and serve requests on a background thread
+    ///
+    /// Bind to port 0 to pick a free port; see [`RpcServerHandle::url`].
+    pub fn spawn(self, addr: impl ToSocketAddrs) -> io::Result<RpcServerHandle> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let stopped = Arc::clone(&stopped);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        self.handle_connection(stream);
+                    }
+                }
+            })
+        };
+
+        Ok(RpcServerHandle {
+            addr,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    fn handle_one(&self, request: &Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or_else(|| json!([]));
+
+        match self.dispatch(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(error) => json!({ "jsonrpc": "2.0", "error": error.to_json(), "id": id }),
+        }
+    }
+
+    fn dispatch(&self, method: &str, params: &Value) -> Result<Value, RpcErrorObject> {
+        handle_request(&mut self.context(), method, params)
+    }
+
+    fn handle_connection(&self, stream: TcpStream) {
+        // A broken connection only affects that client
+        let _ = self.respond(stream);
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+        stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+
+        let (status, body) = if request_line.starts_with("OPTIONS") {
+            // CORS preflight from browser wallets
+            ("204 No Content", String::new())
+        } else if request_line.starts_with("POST") {
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            ("200 OK", self.handle(&String::from_utf8_lossy(&body)))
+        } else {
+            ("405 Method Not Allowed", String::new())
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Access-Control-Allow-Origin: *\r\n\
+             Access-Control-Allow-Methods: POST, OPTIONS\r\n\
+             Access-Control-Allow-Headers: *\r\n\
+             Connection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+}
+
+/// Handle to a server started with [`RpcServer::spawn`]
+pub struct RpcServerHandle {
+    addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RpcServerHandle {
+    /// The address the server is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The HTTP URL to point RPC clients at
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Block until the server thread exits
+    pub fn join(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Stop accepting connections and wait for the server thread to exit
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.stopped.store(true, Ordering::SeqCst);
+            // Wake up the blocking accept
+            let _ = TcpStream::connect(self.addr);
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for RpcServerHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use litesvm::LiteSVM;
+
+    fn server() -> RpcServer {
+        RpcServer::new(AnchorContext::new(LiteSVM::new(), Pubkey::new_unique()))
+    }
+
+    #[test]
+    fn test_batch_request() {
+        let server = server();
+        let user = Pubkey::new_unique();
+
+        let response: Value = serde_json::from_str(&server.handle(
+            &json!([
+                { "jsonrpc": "2.0", "id": 1, "method": "requestAirdrop", "params": [user.to_string(), 1_000] },
+                { "jsonrpc": "2.0", "id": 2, "method": "getBalance", "params": [user.to_string()] },
+                { "jsonrpc": "2.0", "id": 3, "method": "getBlock", "params": [] },
+            ])
+            .to_string(),
+        ))
+        .unwrap();
+
+        assert_eq!(response[1]["id"], 2);
+        assert_eq!(response[1]["result"]["value"], 1_000);
+        assert_eq!(response[2]["error"]["code"], -32601);
+    }
+
+    #[test]
+    fn test_program_accounts_include_helper_accounts() {
+        use crate::TestHelpers;
+        use solana_sdk::signature::Signer;
+
+        let server = server();
+        let mint = {
+            let mut ctx = server.context();
+            let authority = ctx.create_funded_account(1_000_000_000).unwrap();
+            ctx.create_token_mint(&authority, 6).unwrap()
+        };
+
+        let accounts = server
+            .dispatch("getProgramAccounts", &json!([spl_token::id().to_string()]))
+            .unwrap();
+        assert_eq!(accounts.as_array().unwrap().len(), 1);
+        assert_eq!(accounts[0]["pubkey"], mint.pubkey().to_string());
+    }
+
+    #[test]
+    fn test_http_request() {
+        let handle = server().spawn("127.0.0.1:0").unwrap();
+
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "getHealth" }).to_string();
+        let mut stream = TcpStream::connect(handle.local_addr()).unwrap();
+        write!(
+            stream,
+            "POST / HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(r#"{"id":1,"jsonrpc":"2.0","result":"ok"}"#));
+
+        handle.shutdown();
+    }
+
+    #[test]
+    fn test_stalled_connection_does_not_block_server() {
+        let handle = server().spawn("127.0.0.1:0").unwrap();
+
+        // Connects but never sends a request
+        let _stalled = TcpStream::connect(handle.local_addr()).unwrap();
+
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "getHealth" }).to_string();
+        let mut stream = TcpStream::connect(handle.local_addr()).unwrap();
+        write!(
+            stream,
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        handle.shutdown();
+    }
+}
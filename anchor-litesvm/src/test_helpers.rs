@@ -1,13 +1,15 @@
 //! Test helper utilities for common account operations
 //!
 //! This module provides convenient methods for creating and managing test accounts,
-//! token mints, and associated token accounts.
+//! token mints, and associated token accounts. Token helpers work with both the
+//! SPL Token and Token-2022 programs.
 
 use crate::AnchorContext;
 use solana_program::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
 use solana_sdk::transaction::Transaction;
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::ExtensionType;
 use std::error::Error;
 
 /// Test helper methods for AnchorContext
@@ -41,7 +43,7 @@ pub trait TestHelpers {
         lamports: u64,
     ) -> Result<Vec<Keypair>, Box<dyn Error>>;
 
-    /// Create and initialize a token mint
+    /// Create and initialize an SPL Token mint
     ///
     /// # Example
     /// ```no_run
@@ -59,8 +61,33 @@ pub trait TestHelpers {
         decimals: u8,
     ) -> Result<Keypair, Box<dyn Error>>;
 
+    /// Create and initialize a mint owned by a specific token program
+    ///
+    /// Pass `spl_token::id()` or `spl_token_2022::id()` as `token_program`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::{AnchorContext, TestHelpers};
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// let authority = ctx.create_funded_account(10_000_000_000).unwrap();
+    /// let mint = ctx
+    ///     .create_token_mint_with_program(&authority, 6, &spl_token_2022::id())
+    ///     .unwrap();
+    /// ```
+    fn create_token_mint_with_program(
+        &mut self,
+        authority: &Keypair,
+        decimals: u8,
+        token_program: &Pubkey,
+    ) -> Result<Keypair, Box<dyn Error>>;
+
     /// Create an associated token account and optionally mint tokens to it
     ///
+    /// The token program is taken from the owner of `mint`, so this works for
+    /// both SPL Token and Token-2022 mints.
+    ///
     /// # Example
     /// ```ignore
     /// # use anchor_litesvm::{AnchorContext, TestHelpers};
@@ -84,6 +111,25 @@ pub trait TestHelpers {
         mint_amount: Option<(u64, &Keypair)>, // (amount, mint_authority)
     ) -> Result<Pubkey, Box<dyn Error>>;
 
+    /// Create an associated token account for a specific token program
+    ///
+    /// # Example
+    /// ```ignore
+    /// let ata = ctx.create_token_account_with_program(
+    ///     &owner,
+    ///     &mint.pubkey(),
+    ///     Some((1_000_000, &authority)),
+    ///     &spl_token_2022::id(),
+    /// ).unwrap();
+    /// ```
+    fn create_token_account_with_program(
+        &mut self,
+        owner: &Keypair,
+        mint: &Pubkey,
+        mint_amount: Option<(u64, &Keypair)>, // (amount, mint_authority)
+        token_program: &Pubkey,
+    ) -> Result<Pubkey, Box<dyn Error>>;
+
     /// Airdrop SOL to multiple accounts
     ///
     /// # Example
@@ -105,6 +151,7 @@ impl TestHelpers for AnchorContext {
     fn create_funded_account(&mut self, lamports: u64) -> Result<Keypair, Box<dyn Error>> {
         let keypair = Keypair::new();
         self.svm.airdrop(&keypair.pubkey(), lamports).map_err(|e| format!("Airdrop failed: {:?}", e))?;
+        self.track_accounts([keypair.pubkey()]);
         Ok(keypair)
     }
 
@@ -125,21 +172,29 @@ impl TestHelpers for AnchorContext {
         authority: &Keypair,
         decimals: u8,
     ) -> Result<Keypair, Box<dyn Error>> {
-        use litesvm_token::spl_token;
+        self.create_token_mint_with_program(authority, decimals, &spl_token::id())
+    }
 
+    fn create_token_mint_with_program(
+        &mut self,
+        authority: &Keypair,
+        decimals: u8,
+        token_program: &Pubkey,
+    ) -> Result<Keypair, Box<dyn Error>> {
         let mint = Keypair::new();
-        let rent = self.svm.minimum_balance_for_rent_exemption(82); // Mint::LEN
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[])?;
+        let rent = self.svm.minimum_balance_for_rent_exemption(space);
 
         let instructions = vec![
             solana_sdk::system_instruction::create_account(
                 &authority.pubkey(),
                 &mint.pubkey(),
                 rent,
-                82,
-                &spl_token::id(),
+                space as u64,
+                token_program,
             ),
-            spl_token::instruction::initialize_mint(
-                &spl_token::id(),
+            spl_token_2022::instruction::initialize_mint(
+                token_program,
                 &mint.pubkey(),
                 &authority.pubkey(),
                 None,
@@ -154,7 +209,7 @@ impl TestHelpers for AnchorContext {
             self.svm.latest_blockhash(),
         );
 
-        self.svm.send_transaction(tx).map_err(|e| format!("Transaction failed: {:?}", e))?;
+        self.send_transaction(tx).map_err(|e| format!("Transaction failed: {:?}", e))?;
         Ok(mint)
     }
 
@@ -164,9 +219,22 @@ impl TestHelpers for AnchorContext {
         mint: &Pubkey,
         mint_amount: Option<(u64, &Keypair)>,
     ) -> Result<Pubkey, Box<dyn Error>> {
-        use litesvm_token::spl_token;
+        let token_program = self
+            .svm
+            .get_account(mint)
+            .map(|account| account.owner)
+            .ok_or_else(|| format!("Mint {} not found", mint))?;
+        self.create_token_account_with_program(owner, mint, mint_amount, &token_program)
+    }
 
-        let ata = get_associated_token_address(&owner.pubkey(), mint);
+    fn create_token_account_with_program(
+        &mut self,
+        owner: &Keypair,
+        mint: &Pubkey,
+        mint_amount: Option<(u64, &Keypair)>,
+        token_program: &Pubkey,
+    ) -> Result<Pubkey, Box<dyn Error>> {
+        let ata = get_associated_token_address_with_program_id(&owner.pubkey(), mint, token_program);
 
         // Create ATA
         let create_ata_ix =
@@ -174,7 +242,7 @@ impl TestHelpers for AnchorContext {
                 &owner.pubkey(),
                 &owner.pubkey(),
                 mint,
-                &spl_token::id(),
+                token_program,
             );
 
         let tx = Transaction::new_signed_with_payer(
@@ -183,12 +251,12 @@ impl TestHelpers for AnchorContext {
             &[owner],
             self.svm.latest_blockhash(),
         );
-        self.svm.send_transaction(tx).map_err(|e| format!("Transaction failed: {:?}", e))?;
+        self.send_transaction(tx).map_err(|e| format!("Transaction failed: {:?}", e))?;
 
         // Mint tokens if requested
         if let Some((amount, mint_authority)) = mint_amount {
-            let mint_to_ix = spl_token::instruction::mint_to(
-                &spl_token::id(),
+            let mint_to_ix = spl_token_2022::instruction::mint_to(
+                token_program,
                 mint,
                 &ata,
                 &mint_authority.pubkey(),
@@ -202,7 +270,7 @@ impl TestHelpers for AnchorContext {
                 &[mint_authority],
                 self.svm.latest_blockhash(),
             );
-            self.svm.send_transaction(tx).map_err(|e| format!("Transaction failed: {:?}", e))?;
+            self.send_transaction(tx).map_err(|e| format!("Transaction failed: {:?}", e))?;
         }
 
         Ok(ata)
@@ -216,15 +284,36 @@ impl TestHelpers for AnchorContext {
         for pubkey in pubkeys {
             self.svm.airdrop(pubkey, lamports).map_err(|e| format!("Airdrop failed: {:?}", e))?;
         }
+        self.track_accounts(pubkeys.iter().map(|pubkey| **pubkey));
         Ok(())
     }
 }
 
 /// Additional helper functions for token operations
+///
+/// Token accounts and mints are unpacked as Token-2022 state, which shares its
+/// base layout with SPL Token, so these work for either program and ignore any
+/// extensions.
 pub mod token {
     use super::*;
-    use litesvm_token::spl_token;
-    use solana_program_pack::Pack;
+    use solana_program::program_error::ProgramError;
+    use spl_token_2022::extension::StateWithExtensions;
+    use spl_token_2022::state::{Account, Mint};
+
+    /// Check whether a program ID is SPL Token or Token-2022
+    pub fn is_token_program(program_id: &Pubkey) -> bool {
+        *program_id == spl_token::id() || *program_id == spl_token_2022::id()
+    }
+
+    /// Unpack an SPL Token or Token-2022 token account
+    pub fn unpack_token_account(data: &[u8]) -> Result<Account, ProgramError> {
+        StateWithExtensions::<Account>::unpack(data).map(|state| state.base)
+    }
+
+    /// Unpack an SPL Token or Token-2022 mint
+    pub fn unpack_mint(data: &[u8]) -> Result<Mint, ProgramError> {
+        StateWithExtensions::<Mint>::unpack(data).map(|state| state.base)
+    }
 
     /// Get the balance of a token account
     pub fn get_token_balance(
@@ -235,7 +324,7 @@ pub mod token {
             .svm
             .get_account(token_account)
             .ok_or("Token account not found")?;
-        let token_account = spl_token::state::Account::unpack(&account.data)?;
+        let token_account = unpack_token_account(&account.data)?;
         Ok(token_account.amount)
     }
 
@@ -243,8 +332,14 @@ pub mod token {
     pub fn get_token_balance_safe(ctx: &AnchorContext, token_account: &Pubkey) -> u64 {
         ctx.svm
             .get_account(token_account)
-            .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+            .and_then(|account| unpack_token_account(&account.data).ok())
             .map(|token_account| token_account.amount)
             .unwrap_or(0)
     }
-}
\ No newline at end of file
+
+    /// Get a mint's state
+    pub fn get_mint(ctx: &AnchorContext, mint: &Pubkey) -> Result<Mint, Box<dyn Error>> {
+        let account = ctx.svm.get_account(mint).ok_or("Mint not found")?;
+        Ok(unpack_mint(&account.data)?)
+    }
+}
@@ -0,0 +1,182 @@
+//! Clock and slot time-travel helpers
+//!
+//! This module provides methods for moving the `Clock` sysvar forward so that
+//! time-locked logic (vesting, expiring escrows, cooldowns) can be tested.
+//! Every helper keeps `slot`, `epoch`, `leader_schedule_epoch`,
+//! `epoch_start_timestamp` and `unix_timestamp` consistent with each other
+//! and expires the current blockhash.
+
+use crate::AnchorContext;
+use solana_program::clock::{Clock, DEFAULT_MS_PER_SLOT};
+use solana_program::epoch_schedule::EpochSchedule;
+use std::time::Duration;
+
+/// Time manipulation methods for AnchorContext
+pub trait TimeHelpers {
+    /// Get the current `Clock` sysvar
+    fn clock(&self) -> Clock;
+
+    /// Warp to a specific slot
+    ///
+    /// The epoch and leader schedule epoch are derived from the `EpochSchedule`
+    /// sysvar. When moving forward, `unix_timestamp` advances by 400ms per slot.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::{AnchorContext, TimeHelpers};
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// ctx.warp_to_slot(1_000);
+    /// assert_eq!(ctx.clock().slot, 1_000);
+    /// ```
+    fn warp_to_slot(&mut self, slot: u64);
+
+    /// Advance the clock by a duration
+    ///
+    /// `unix_timestamp` moves forward by the whole seconds in `duration` and
+    /// the slot by the matching number of 400ms slots.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::{AnchorContext, TimeHelpers};
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # use std::time::Duration;
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// // Skip past a one day time lock
+    /// ctx.advance_time(Duration::from_secs(24 * 60 * 60));
+    /// ```
+    fn advance_time(&mut self, duration: Duration);
+
+    /// Set `unix_timestamp` without changing the slot
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::{AnchorContext, TimeHelpers};
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// ctx.set_unix_timestamp(1_700_000_000);
+    /// ```
+    fn set_unix_timestamp(&mut self, unix_timestamp: i64);
+
+    /// Warp to the first slot of the next epoch
+    fn advance_epoch(&mut self);
+}
+
+impl TimeHelpers for AnchorContext {
+    fn clock(&self) -> Clock {
+        self.svm.get_sysvar::<Clock>()
+    }
+
+    fn warp_to_slot(&mut self, slot: u64) {
+        let clock = self.clock();
+        let elapsed_ms = slot.saturating_sub(clock.slot) * DEFAULT_MS_PER_SLOT;
+        let unix_timestamp = clock.unix_timestamp + (elapsed_ms / 1_000) as i64;
+        set_clock(self, slot, unix_timestamp);
+    }
+
+    fn advance_time(&mut self, duration: Duration) {
+        let clock = self.clock();
+        let slots = duration.as_millis() as u64 / DEFAULT_MS_PER_SLOT;
+        let unix_timestamp = clock.unix_timestamp + duration.as_secs() as i64;
+        set_clock(self, clock.slot + slots, unix_timestamp);
+    }
+
+    fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
+        let slot = self.clock().slot;
+        set_clock(self, slot, unix_timestamp);
+    }
+
+    fn advance_epoch(&mut self) {
+        let schedule = self.svm.get_sysvar::<EpochSchedule>();
+        let next_epoch = self.clock().epoch + 1;
+        self.warp_to_slot(schedule.get_first_slot_in_epoch(next_epoch));
+    }
+}
+
+/// Write a consistent `Clock` for the given slot and timestamp
+fn set_clock(ctx: &mut AnchorContext, slot: u64, unix_timestamp: i64) {
+    let schedule = ctx.svm.get_sysvar::<EpochSchedule>();
+    let mut clock = ctx.svm.get_sysvar::<Clock>();
+
+    let epoch = schedule.get_epoch(slot);
+    if epoch != clock.epoch {
+        // Approximate the epoch start by walking back from the new slot
+        let slots_into_epoch = slot - schedule.get_first_slot_in_epoch(epoch);
+        clock.epoch_start_timestamp =
+            unix_timestamp - (slots_into_epoch * DEFAULT_MS_PER_SLOT / 1_000) as i64;
+    }
+
+    clock.slot = slot;
+    clock.epoch = epoch;
+    clock.leader_schedule_epoch = schedule.get_leader_schedule_epoch(slot);
+    clock.unix_timestamp = unix_timestamp;
+
+    ctx.svm.set_sysvar::<Clock>(&clock);
+    ctx.svm.expire_blockhash();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use litesvm::LiteSVM;
+    use solana_program::pubkey::Pubkey;
+
+    fn context() -> AnchorContext {
+        AnchorContext::new(LiteSVM::new(), Pubkey::new_unique())
+    }
+
+    #[test]
+    fn test_warp_to_slot() {
+        let mut ctx = context();
+        let before = ctx.clock();
+        let blockhash = ctx.svm.latest_blockhash();
+
+        ctx.warp_to_slot(before.slot + 1_000);
+
+        let after = ctx.clock();
+        assert_eq!(after.slot, before.slot + 1_000);
+        assert_eq!(after.unix_timestamp, before.unix_timestamp + 400);
+        assert_ne!(ctx.svm.latest_blockhash(), blockhash);
+    }
+
+    #[test]
+    fn test_advance_time() {
+        let mut ctx = context();
+        let before = ctx.clock();
+
+        ctx.advance_time(Duration::from_secs(60));
+
+        let after = ctx.clock();
+        assert_eq!(after.unix_timestamp, before.unix_timestamp + 60);
+        assert_eq!(after.slot, before.slot + 150);
+    }
+
+    #[test]
+    fn test_set_unix_timestamp() {
+        let mut ctx = context();
+        let slot = ctx.clock().slot;
+
+        ctx.set_unix_timestamp(1_700_000_000);
+
+        assert_eq!(ctx.clock().unix_timestamp, 1_700_000_000);
+        assert_eq!(ctx.clock().slot, slot);
+    }
+
+    #[test]
+    fn test_advance_epoch() {
+        let mut ctx = context();
+        let schedule = ctx.svm.get_sysvar::<EpochSchedule>();
+        let epoch = ctx.clock().epoch;
+
+        ctx.advance_epoch();
+
+        let clock = ctx.clock();
+        assert_eq!(clock.epoch, epoch + 1);
+        assert_eq!(clock.slot, schedule.get_first_slot_in_epoch(epoch + 1));
+        assert_eq!(clock.leader_schedule_epoch, schedule.get_leader_schedule_epoch(clock.slot));
+        assert_eq!(clock.epoch_start_timestamp, clock.unix_timestamp);
+    }
+}
@@ -3,11 +3,45 @@
 //! This module provides convenient wrappers for executing transactions
 //! and handling their results in tests.
 
-use litesvm::types::TransactionMetadata;
+use crate::call_tree::CallTree;
+use crate::cu_baseline::CuBaseline;
+use crate::events::{parse_cpi_events, parse_log_events};
+use anchor_lang::AnchorDeserialize;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use litesvm::types::{FailedTransactionMetadata, SimulatedTransactionInfo, TransactionMetadata};
 use solana_program::instruction::Instruction;
+use solana_program::message::inner_instruction::InnerInstructionsList;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
 use solana_sdk::signature::{Keypair, Signer};
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{Transaction, TransactionError as SdkTransactionError};
 use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// Data a program returned with `set_return_data`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReturnData {
+    /// The program that set the return data
+    pub program_id: Pubkey,
+    /// The raw returned bytes
+    pub data: Vec<u8>,
+}
+
+impl ReturnData {
+    /// Parse the last `Program return: <program_id> <base64>` log line
+    pub fn from_logs(logs: &[String]) -> Option<Self> {
+        logs.iter().rev().find_map(|log| {
+            let rest = log.strip_prefix("Program return: ")?;
+            let (program_id, data) = rest.split_once(' ')?;
+            Some(Self {
+                program_id: Pubkey::from_str(program_id).ok()?,
+                data: STANDARD.decode(data.trim()).ok()?,
+            })
+        })
+    }
+}
 
 /// Wrapper around LiteSVM's TransactionMetadata with helper methods for testing
 ///
@@ -25,6 +59,9 @@ use std::fmt;
 pub struct TransactionResult {
     inner: TransactionMetadata,
     instruction_name: Option<String>,
+    post_accounts: Vec<(Pubkey, Account)>,
+    instruction_programs: Vec<u8>,
+    instruction_data: Vec<Vec<u8>>,
 }
 
 impl TransactionResult {
@@ -33,14 +70,38 @@ impl TransactionResult {
     /// # Arguments
     ///
     /// * `result` - The transaction metadata from LiteSVM
+    /// * `tx` - The executed transaction, whose top-level instructions are
+    ///   needed to tell `emit_cpi!` self-CPIs apart in [`Self::events`] and to
+    ///   name top-level calls in [`CallTree::with_idl_names`]
     /// * `instruction_name` - Optional name of the instruction for debugging
-    pub fn new(result: TransactionMetadata, instruction_name: Option<String>) -> Self {
+    pub fn new(result: TransactionMetadata, tx: &Transaction, instruction_name: Option<String>) -> Self {
         Self {
             inner: result,
             instruction_name,
+            post_accounts: Vec::new(),
+            instruction_programs: instruction_programs(tx),
+            instruction_data: instruction_data(tx),
         }
     }
 
+    /// Wrap the result of simulating `tx`, keeping the post-execution account states
+    pub(crate) fn simulated(info: SimulatedTransactionInfo, tx: &Transaction) -> Self {
+        Self {
+            post_accounts: info
+                .post_accounts
+                .into_iter()
+                .map(|(pubkey, account)| (pubkey, account.into()))
+                .collect(),
+            ..Self::new(info.meta, tx, None)
+        }
+    }
+
+    /// Attach the name of the executed instruction for debugging
+    pub(crate) fn with_instruction_name(mut self, instruction_name: String) -> Self {
+        self.instruction_name = Some(instruction_name);
+        self
+    }
+
     /// Assert that the transaction succeeded, panic with logs if it failed
     ///
     /// # Returns
@@ -105,13 +166,11 @@ impl TransactionResult {
         self.inner.logs.iter().any(|log| log.contains(pattern))
     }
 
-    /// Get the compute units consumed
-    ///
-    /// Parses the compute units from the transaction logs.
+    /// Get the compute units consumed by the whole transaction
     ///
     /// # Returns
     ///
-    /// Returns the number of compute units consumed, or 0 if not found
+    /// Returns the total reported by LiteSVM, including all instructions and CPIs
     ///
     /// # Example
     ///
@@ -120,20 +179,126 @@ impl TransactionResult {
     /// assert!(cu < 200_000, "Used too many compute units: {}", cu);
     /// ```
     pub fn compute_units(&self) -> u64 {
-        // Parse compute units from logs
-        for log in &self.inner.logs {
-            if log.contains("consumed") && log.contains("compute units") {
-                // Extract number from log like "Program ... consumed 12345 of 200000 compute units"
-                if let Some(consumed_part) = log.split("consumed").nth(1) {
-                    if let Some(number_part) = consumed_part.split("of").next() {
-                        if let Ok(units) = number_part.trim().parse::<u64>() {
-                            return units;
-                        }
-                    }
-                }
-            }
+        self.inner.compute_units_consumed
+    }
+
+    /// Get the compute units of each top-level instruction, including its CPIs
+    ///
+    /// See [`CallTree::instruction_compute_units`].
+    pub fn instruction_compute_units(&self) -> Vec<u64> {
+        self.call_tree().instruction_compute_units()
+    }
+
+    /// Get the compute units used by each program itself, highest first
+    ///
+    /// See [`CallTree::compute_units_by_program`].
+    pub fn compute_units_by_program(&self) -> Vec<(Pubkey, u64)> {
+        self.call_tree().compute_units_by_program()
+    }
+
+    /// Get the compute units not attributed to any program
+    ///
+    /// Top-level instructions of builtin programs, such as the system and
+    /// compute budget programs, log no usage but still count towards
+    /// [`Self::compute_units`]. This is the difference between the total and
+    /// the sum of [`Self::compute_units_by_program`].
+    pub fn unattributed_compute_units(&self) -> u64 {
+        unattributed_compute_units(self.compute_units(), &self.call_tree())
+    }
+
+    /// Get all Anchor events of type `T` emitted by the transaction
+    ///
+    /// Decodes both `emit!` events (`Program data:` log lines) and `emit_cpi!`
+    /// events (self-CPI inner instructions), matching on the event discriminator.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let events = result.events::<EscrowMade>();
+    /// assert_eq!(events.len(), 1);
+    /// assert_eq!(events[0].amount, 1_000_000_000);
+    /// ```
+    pub fn events<T: anchor_lang::Event>(&self) -> Vec<T> {
+        let mut events = parse_log_events(&self.inner.logs);
+        events.extend(parse_cpi_events(
+            &self.instruction_programs,
+            &self.inner.inner_instructions,
+        ));
+        events
+    }
+
+    /// Check the compute units against the default baseline file, panicking on regression
+    ///
+    /// The baseline is `cu_baseline.json` next to the test crate's
+    /// `Cargo.toml`. New names are added to it; set `UPDATE_CU_BASELINE=1` to
+    /// rewrite recorded values. See [`CuBaseline`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// ctx.instruction_builder("take")
+    ///     // ...
+    ///     .execute(&mut ctx, &[&taker])?
+    ///     .record_cu("escrow::take");
+    /// ```
+    pub fn record_cu(&self, name: &str) -> &Self {
+        self.record_cu_with(&CuBaseline::default(), name)
+    }
+
+    /// Check the compute units against a specific baseline, panicking on regression
+    pub fn record_cu_with(&self, baseline: &CuBaseline, name: &str) -> &Self {
+        baseline.assert(name, self.compute_units());
+        self
+    }
+
+    /// Rebuild the nested program invocations of the transaction
+    ///
+    /// Top-level calls carry their instruction data from the transaction and
+    /// CPIs from LiteSVM's inner instructions, so [`CallTree::with_idl_names`]
+    /// can name calls into programs with a loaded IDL.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let tree = result.call_tree().with_idl_names(&ctx);
+    /// println!("{}", tree);
+    /// let hungriest = tree.most_expensive().unwrap();
+    /// ```
+    pub fn call_tree(&self) -> CallTree {
+        call_tree(&self.inner.logs, &self.instruction_data, &self.inner.inner_instructions)
+    }
+
+    /// Get the data returned by the transaction's last `set_return_data` call
+    ///
+    /// Falls back to the `Program return:` log line if LiteSVM did not record
+    /// return data. Returns `None` if no program returned anything.
+    pub fn return_data(&self) -> Option<ReturnData> {
+        let return_data = &self.inner.return_data;
+        if !return_data.data.is_empty() {
+            return Some(ReturnData {
+                program_id: return_data.program_id,
+                data: return_data.data.clone(),
+            });
         }
-        0
+        ReturnData::from_logs(&self.inner.logs)
+    }
+
+    /// Deserialize the returned data of an Anchor instruction
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // pub fn get_price(ctx: Context<GetPrice>) -> Result<u64>
+    /// let result = ctx.instruction_builder("get_price")
+    ///     // ...
+    ///     .simulate(&ctx, &[&user])?;
+    /// assert_eq!(result.return_data_as::<u64>()?, 42);
+    /// ```
+    pub fn return_data_as<T: AnchorDeserialize>(&self) -> io::Result<T> {
+        let return_data = self.return_data().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Transaction has no return data")
+        })?;
+        T::try_from_slice(&return_data.data)
     }
 
     /// Print transaction logs (useful for debugging)
@@ -150,6 +315,30 @@ impl TransactionResult {
         }
     }
 
+    /// Account states after a simulated transaction
+    ///
+    /// Lists every account of the transaction as it would be after execution.
+    /// Empty for committed transactions; read those from LiteSVM directly.
+    pub fn post_accounts(&self) -> &[(Pubkey, Account)] {
+        &self.post_accounts
+    }
+
+    /// State of one account after a simulated transaction
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let result = ctx.simulate_instruction(ix, &[&taker])?;
+    /// let vault = result.post_account(&vault).unwrap();
+    /// assert_eq!(vault.lamports, 0);
+    /// ```
+    pub fn post_account(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.post_accounts
+            .iter()
+            .find(|(key, _)| key == pubkey)
+            .map(|(_, account)| account)
+    }
+
     /// Get the inner LiteSVM result
     pub fn inner(&self) -> &TransactionMetadata {
         &self.inner
@@ -167,19 +356,198 @@ impl fmt::Debug for TransactionResult {
 }
 
 /// Error type for transaction execution
+///
+/// A failed execution keeps LiteSVM's metadata, so a failing transaction can be
+/// inspected with the same `logs()` / `find_logs()` / `compute_units()` API as
+/// a successful [`TransactionResult`].
+///
+/// # Example
+///
+/// ```ignore
+/// let err = ctx.send_instruction(ix, &[&signer]).unwrap_err();
+/// assert_eq!(err.instruction_index(), Some(0));
+/// assert!(err.has_log("insufficient funds"));
+/// err.print_logs();
+/// ```
 #[derive(Debug)]
 pub enum TransactionError {
-    /// Transaction failed with error message
-    ExecutionFailed(String),
+    /// Transaction was executed and failed
+    ExecutionFailed {
+        /// The runtime error returned by LiteSVM
+        error: SdkTransactionError,
+        /// Index of the instruction that failed, if the error is instruction-specific
+        instruction_index: Option<u8>,
+        /// Log messages emitted before the failure
+        logs: Vec<String>,
+        /// Compute units consumed before the failure
+        compute_units: u64,
+        /// CPIs recorded before the failure, per top-level instruction
+        inner_instructions: InnerInstructionsList,
+        /// Data of each top-level instruction, empty if the transaction is unknown
+        instruction_data: Vec<Vec<u8>>,
+    },
     /// Error building the transaction
     BuildError(String),
 }
 
+impl TransactionError {
+    /// Get the underlying runtime error, if the transaction was executed
+    pub fn transaction_error(&self) -> Option<&SdkTransactionError> {
+        match self {
+            TransactionError::ExecutionFailed { error, .. } => Some(error),
+            TransactionError::BuildError(_) => None,
+        }
+    }
+
+    /// Get the index of the instruction that failed
+    pub fn instruction_index(&self) -> Option<u8> {
+        match self {
+            TransactionError::ExecutionFailed {
+                instruction_index, ..
+            } => *instruction_index,
+            TransactionError::BuildError(_) => None,
+        }
+    }
+
+    /// Get the transaction logs (empty if the transaction was never executed)
+    pub fn logs(&self) -> &[String] {
+        match self {
+            TransactionError::ExecutionFailed { logs, .. } => logs,
+            TransactionError::BuildError(_) => &[],
+        }
+    }
+
+    /// Get specific log lines that match a pattern
+    pub fn find_logs(&self, pattern: &str) -> Vec<&String> {
+        self.logs()
+            .iter()
+            .filter(|log| log.contains(pattern))
+            .collect()
+    }
+
+    /// Check if a specific log message exists
+    pub fn has_log(&self, pattern: &str) -> bool {
+        self.logs().iter().any(|log| log.contains(pattern))
+    }
+
+    /// Get the compute units consumed before the failure
+    pub fn compute_units(&self) -> u64 {
+        match self {
+            TransactionError::ExecutionFailed { compute_units, .. } => *compute_units,
+            TransactionError::BuildError(_) => 0,
+        }
+    }
+
+    /// Get the compute units of each top-level instruction that ran, including its CPIs
+    pub fn instruction_compute_units(&self) -> Vec<u64> {
+        self.call_tree().instruction_compute_units()
+    }
+
+    /// Get the compute units used by each program itself before the failure, highest first
+    pub fn compute_units_by_program(&self) -> Vec<(Pubkey, u64)> {
+        self.call_tree().compute_units_by_program()
+    }
+
+    /// Get the compute units consumed before the failure but not attributed to any program
+    ///
+    /// See [`TransactionResult::unattributed_compute_units`].
+    pub fn unattributed_compute_units(&self) -> u64 {
+        unattributed_compute_units(self.compute_units(), &self.call_tree())
+    }
+
+    /// Rebuild the nested program invocations that ran before the failure
+    ///
+    /// [`CallTree::failed`] points at the call where the error originated.
+    /// As for [`TransactionResult::call_tree`], calls carry their instruction data.
+    pub fn call_tree(&self) -> CallTree {
+        match self {
+            TransactionError::ExecutionFailed {
+                logs,
+                inner_instructions,
+                instruction_data,
+                ..
+            } => call_tree(logs, instruction_data, inner_instructions),
+            TransactionError::BuildError(_) => CallTree::from_logs(&[]),
+        }
+    }
+
+    /// Record the data of each top-level instruction of `tx` for [`Self::call_tree`]
+    pub(crate) fn with_transaction(mut self, tx: &Transaction) -> Self {
+        if let TransactionError::ExecutionFailed {
+            instruction_data: data,
+            ..
+        } = &mut self
+        {
+            *data = instruction_data(tx);
+        }
+        self
+    }
+
+    /// Print transaction logs (useful for debugging)
+    pub fn print_logs(&self) {
+        println!("Transaction logs ({}):", self);
+        for log in self.logs() {
+            println!("  {}", log);
+        }
+    }
+}
+
+impl From<FailedTransactionMetadata> for TransactionError {
+    fn from(failed: FailedTransactionMetadata) -> Self {
+        let instruction_index = match &failed.err {
+            SdkTransactionError::InstructionError(index, _) => Some(*index),
+            _ => None,
+        };
+        TransactionError::ExecutionFailed {
+            error: failed.err,
+            instruction_index,
+            logs: failed.meta.logs,
+            compute_units: failed.meta.compute_units_consumed,
+            inner_instructions: failed.meta.inner_instructions,
+            instruction_data: Vec::new(),
+        }
+    }
+}
+
+/// Usage of `total` missing from the per-program breakdown of `tree`
+fn unattributed_compute_units(total: u64, tree: &CallTree) -> u64 {
+    let attributed: u64 = tree.compute_units_by_program().iter().map(|(_, units)| units).sum();
+    total.saturating_sub(attributed)
+}
+
+/// Program index of each top-level instruction of `tx`
+fn instruction_programs(tx: &Transaction) -> Vec<u8> {
+    tx.message.instructions.iter().map(|ix| ix.program_id_index).collect()
+}
+
+/// Data of each top-level instruction of `tx`
+fn instruction_data(tx: &Transaction) -> Vec<Vec<u8>> {
+    tx.message.instructions.iter().map(|ix| ix.data.clone()).collect()
+}
+
+/// Build a call tree whose calls carry their instruction data
+fn call_tree(
+    logs: &[String],
+    instruction_data: &[Vec<u8>],
+    inner_instructions: &InnerInstructionsList,
+) -> CallTree {
+    let inner_instructions: Vec<Vec<(u8, Vec<u8>)>> = inner_instructions
+        .iter()
+        .map(|inner| {
+            inner
+                .iter()
+                .map(|ix| (ix.stack_height, ix.instruction.data.clone()))
+                .collect()
+        })
+        .collect();
+    CallTree::from_logs_and_inner(logs, instruction_data, &inner_instructions)
+}
+
 impl fmt::Display for TransactionError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            TransactionError::ExecutionFailed(msg) => {
-                write!(f, "Transaction execution failed: {}", msg)
+            TransactionError::ExecutionFailed { error, .. } => {
+                write!(f, "Transaction execution failed: {}", error)
             }
             TransactionError::BuildError(msg) => {
                 write!(f, "Transaction build error: {}", msg)
@@ -188,7 +556,14 @@ impl fmt::Display for TransactionError {
     }
 }
 
-impl std::error::Error for TransactionError {}
+impl std::error::Error for TransactionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TransactionError::ExecutionFailed { error, .. } => Some(error),
+            TransactionError::BuildError(_) => None,
+        }
+    }
+}
 
 /// Helper trait for transaction execution on AnchorContext
 pub trait TransactionHelpers {
@@ -241,6 +616,41 @@ pub trait TransactionHelpers {
         signers: &[&Keypair],
     ) -> Result<TransactionResult, TransactionError>;
 
+    /// Simulate a single instruction without changing any state
+    ///
+    /// Returns the same result as [`send_instruction`](Self::send_instruction)
+    /// would, with post-execution account states available through
+    /// [`TransactionResult::post_accounts`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use anchor_litesvm::{AnchorContext, TransactionHelpers};
+    /// # use litesvm::LiteSVM;
+    /// # use solana_program::pubkey::Pubkey;
+    /// # use solana_sdk::signature::Keypair;
+    /// # let ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+    /// # let signer = Keypair::new();
+    /// # let ix = solana_program::instruction::Instruction {
+    /// #     program_id: Pubkey::new_unique(),
+    /// #     accounts: vec![],
+    /// #     data: vec![],
+    /// # };
+    /// let result = ctx.simulate_instruction(ix, &[&signer]).unwrap();
+    /// println!("Would use {} compute units", result.compute_units());
+    /// ```
+    fn simulate_instruction(
+        &self,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<TransactionResult, TransactionError>;
+
+    /// Simulate multiple instructions as a single transaction without changing any state
+    fn simulate_instructions(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<TransactionResult, TransactionError>;
+
     /// Build and execute an instruction in one call
     ///
     /// # Example
@@ -293,25 +703,32 @@ impl TransactionHelpers for crate::AnchorContext {
         instructions: &[Instruction],
         signers: &[&Keypair],
     ) -> Result<TransactionResult, TransactionError> {
-        if signers.is_empty() {
-            return Err(TransactionError::BuildError(
-                "No signers provided".to_string(),
-            ));
+        let tx = signed_transaction(self, instructions, signers)?;
+
+        match self.send_transaction(tx.clone()) {
+            Ok(result) => Ok(TransactionResult::new(result, &tx, None)),
+            Err(e) => Err(TransactionError::from(e).with_transaction(&tx)),
         }
+    }
 
-        // Use first signer as payer
-        let payer = &signers[0].pubkey();
+    fn simulate_instruction(
+        &self,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<TransactionResult, TransactionError> {
+        self.simulate_instructions(&[instruction], signers)
+    }
 
-        let tx = Transaction::new_signed_with_payer(
-            instructions,
-            Some(payer),
-            signers,
-            self.svm.latest_blockhash(),
-        );
+    fn simulate_instructions(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<TransactionResult, TransactionError> {
+        let tx = signed_transaction(self, instructions, signers)?;
 
-        match self.svm.send_transaction(tx) {
-            Ok(result) => Ok(TransactionResult::new(result, None)),
-            Err(e) => Err(TransactionError::ExecutionFailed(format!("{:?}", e))),
+        match self.svm.simulate_transaction(tx.clone()) {
+            Ok(info) => Ok(TransactionResult::simulated(info, &tx)),
+            Err(e) => Err(TransactionError::from(e).with_transaction(&tx)),
         }
     }
 
@@ -337,4 +754,309 @@ impl TransactionHelpers for crate::AnchorContext {
             Err(e) => Err(e),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Sign a transaction with the latest blockhash, using the first signer as payer
+fn signed_transaction(
+    ctx: &crate::AnchorContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<Transaction, TransactionError> {
+    let payer = signers
+        .first()
+        .ok_or_else(|| TransactionError::BuildError("No signers provided".to_string()))?;
+
+    Ok(Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        signers,
+        ctx.svm.latest_blockhash(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnchorContext;
+    use litesvm::LiteSVM;
+    use solana_program::program_pack::Pack;
+    use solana_sdk::instruction::InstructionError;
+    use solana_sdk::{system_instruction, system_program};
+
+    #[test]
+    fn test_execution_failed_from_metadata() {
+        let failed = FailedTransactionMetadata {
+            err: SdkTransactionError::InstructionError(1, InstructionError::Custom(6000)),
+            meta: TransactionMetadata {
+                logs: vec![
+                    "Program log: Instruction: Take".to_string(),
+                    "Program log: Error: insufficient funds".to_string(),
+                ],
+                compute_units_consumed: 4_321,
+                ..Default::default()
+            },
+        };
+
+        let err = TransactionError::from(failed);
+        assert_eq!(err.instruction_index(), Some(1));
+        assert_eq!(err.compute_units(), 4_321);
+        assert_eq!(err.logs().len(), 2);
+        assert!(err.has_log("insufficient funds"));
+        assert_eq!(err.find_logs("Instruction:").len(), 1);
+        assert!(matches!(
+            err.transaction_error(),
+            Some(SdkTransactionError::InstructionError(1, InstructionError::Custom(6000)))
+        ));
+        assert!(err.to_string().starts_with("Transaction execution failed"));
+    }
+
+    #[test]
+    fn test_failed_call_tree_has_inner_instruction_data() {
+        use solana_program::message::compiled_instruction::CompiledInstruction;
+        use solana_program::message::inner_instruction::InnerInstruction;
+
+        let program = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let failed = FailedTransactionMetadata {
+            err: SdkTransactionError::InstructionError(0, InstructionError::Custom(1)),
+            meta: TransactionMetadata {
+                logs: vec![
+                    format!("Program {} invoke [1]", program),
+                    format!("Program {} invoke [2]", token),
+                    format!("Program {} failed: custom program error: 0x1", token),
+                    format!("Program {} failed: custom program error: 0x1", program),
+                ],
+                inner_instructions: vec![vec![InnerInstruction {
+                    instruction: CompiledInstruction::new_from_raw_parts(2, vec![12, 1], vec![]),
+                    stack_height: 2,
+                }]],
+                ..Default::default()
+            },
+        };
+
+        let tree = TransactionError::from(failed).call_tree();
+        let failed = tree.failed().unwrap();
+        assert_eq!(failed.program_id, token);
+        assert_eq!(failed.data, Some(vec![12, 1]));
+    }
+
+    #[test]
+    fn test_call_tree_has_top_level_instruction_data() {
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let payer = Keypair::new();
+        ctx.svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+        let ix = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1_000_000);
+
+        let result = ctx.send_instruction(ix.clone(), &[&payer]).unwrap();
+        assert_eq!(result.call_tree().roots[0].data, Some(ix.data));
+
+        // Failed transactions keep it too
+        let ix = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 10_000_000_000);
+        let err = ctx.send_instruction(ix.clone(), &[&payer]).unwrap_err();
+        let tree = err.call_tree();
+        assert_eq!(tree.failed().unwrap().program_id, system_program::id());
+        assert_eq!(tree.roots[0].data, Some(ix.data));
+    }
+
+    #[test]
+    fn test_simulate_does_not_change_state() {
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        ctx.svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+        let ix = system_instruction::transfer(&payer.pubkey(), &recipient, 1_000_000);
+
+        let result = ctx.simulate_instruction(ix.clone(), &[&payer]).unwrap();
+        assert_eq!(result.post_account(&recipient).unwrap().lamports, 1_000_000);
+        assert_eq!(ctx.svm.get_balance(&recipient), None);
+        assert_eq!(ctx.svm.get_balance(&payer.pubkey()), Some(1_000_000_000));
+
+        // The simulated transaction can still be sent afterwards
+        let result = ctx.send_instruction(ix, &[&payer]).unwrap();
+        assert!(result.post_accounts().is_empty());
+        assert_eq!(ctx.svm.get_balance(&recipient), Some(1_000_000));
+    }
+
+    #[test]
+    fn test_simulate_failure() {
+        let ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let payer = Keypair::new();
+        let ix = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+
+        // Unfunded payer
+        let err = ctx.simulate_instruction(ix.clone(), &[&payer]).unwrap_err();
+        assert!(err.transaction_error().is_some());
+        assert!(matches!(
+            ctx.simulate_instruction(ix, &[]),
+            Err(TransactionError::BuildError(_))
+        ));
+    }
+
+    #[test]
+    fn test_compute_units() {
+        let program = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let meta = TransactionMetadata {
+            logs: vec![
+                format!("Program {} invoke [1]", program),
+                format!("Program {} invoke [2]", token),
+                format!("Program {} consumed 4000 of 195000 compute units", token),
+                format!("Program {} success", token),
+                format!("Program {} consumed 12000 of 200000 compute units", program),
+                format!("Program {} success", program),
+            ],
+            compute_units_consumed: 12_150,
+            ..Default::default()
+        };
+
+        // The total comes from LiteSVM, not the innermost CPI's log line
+        let result = TransactionResult::new(meta, &Transaction::default(), None);
+        assert_eq!(result.compute_units(), 12_150);
+        assert_eq!(result.instruction_compute_units(), vec![12_000]);
+        assert_eq!(
+            result.compute_units_by_program(),
+            vec![(program, 8_000), (token, 4_000)]
+        );
+        assert_eq!(result.unattributed_compute_units(), 150);
+    }
+
+    #[test]
+    fn test_builtin_compute_units_are_unattributed() {
+        let mut ctx = AnchorContext::new(LiteSVM::new(), Pubkey::new_unique());
+        let payer = Keypair::new();
+        let mint = Keypair::new();
+        ctx.svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+        let space = spl_token::state::Mint::LEN;
+        let rent = ctx.svm.minimum_balance_for_rent_exemption(space);
+
+        // System program instructions on either side of a token program instruction
+        let result = ctx
+            .send_instructions(
+                &[
+                    system_instruction::create_account(
+                        &payer.pubkey(),
+                        &mint.pubkey(),
+                        rent,
+                        space as u64,
+                        &spl_token::id(),
+                    ),
+                    spl_token::instruction::initialize_mint2(
+                        &spl_token::id(),
+                        &mint.pubkey(),
+                        &payer.pubkey(),
+                        None,
+                        6,
+                    )
+                    .unwrap(),
+                    system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1_000_000),
+                ],
+                &[&payer, &mint],
+            )
+            .unwrap();
+
+        let token_units = result.instruction_compute_units()[1];
+        assert!(token_units > 0);
+        assert_eq!(result.instruction_compute_units(), vec![0, token_units, 0]);
+        assert_eq!(
+            result.compute_units_by_program(),
+            vec![(spl_token::id(), token_units), (system_program::id(), 0)]
+        );
+        // 150 units for each system program instruction
+        assert_eq!(result.unattributed_compute_units(), 300);
+        assert_eq!(
+            token_units + result.unattributed_compute_units(),
+            result.compute_units()
+        );
+    }
+
+    #[test]
+    fn test_events_from_self_cpi() {
+        use anchor_lang::event::EVENT_IX_TAG_LE;
+        use anchor_lang::prelude::*;
+        use anchor_lang::Event;
+        use solana_program::message::compiled_instruction::CompiledInstruction;
+        use solana_program::message::inner_instruction::InnerInstruction;
+        use solana_sdk::message::Message;
+
+        #[event]
+        #[derive(Debug, PartialEq)]
+        struct Deposited {
+            amount: u64,
+        }
+
+        let program = Pubkey::new_unique();
+        let tx = Transaction::new_unsigned(Message::new(
+            &[Instruction::new_with_bytes(program, &[1], vec![])],
+            None,
+        ));
+        let mut data = EVENT_IX_TAG_LE.to_vec();
+        data.extend_from_slice(&Deposited { amount: 5 }.data());
+        let meta = TransactionMetadata {
+            inner_instructions: vec![vec![InnerInstruction {
+                instruction: CompiledInstruction::new_from_raw_parts(0, data, vec![]),
+                stack_height: 2,
+            }]],
+            ..Default::default()
+        };
+
+        let result = TransactionResult::new(meta, &tx, None);
+        assert_eq!(result.events::<Deposited>(), vec![Deposited { amount: 5 }]);
+    }
+
+    #[test]
+    fn test_return_data() {
+        let program_id = Pubkey::new_unique();
+        let mut return_data = TransactionMetadata::default().return_data;
+        return_data.program_id = program_id;
+        return_data.data = 42u64.to_le_bytes().to_vec();
+        let meta = TransactionMetadata {
+            return_data,
+            ..Default::default()
+        };
+
+        let result = TransactionResult::new(meta, &Transaction::default(), None);
+        assert_eq!(result.return_data().unwrap().program_id, program_id);
+        assert_eq!(result.return_data_as::<u64>().unwrap(), 42);
+        assert!(result.return_data_as::<u32>().is_err());
+
+        let empty = TransactionResult::new(TransactionMetadata::default(), &Transaction::default(), None);
+        assert!(empty.return_data().is_none());
+        assert_eq!(
+            empty.return_data_as::<u64>().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_return_data_from_logs() {
+        let program_id = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            format!("Program return: {} {}", program_id, STANDARD.encode([7, 0, 0, 0])),
+            format!("Program {} success", program_id),
+        ];
+        let meta = TransactionMetadata {
+            logs,
+            ..Default::default()
+        };
+
+        let result = TransactionResult::new(meta, &Transaction::default(), None);
+        assert_eq!(
+            result.return_data(),
+            Some(ReturnData {
+                program_id,
+                data: vec![7, 0, 0, 0]
+            })
+        );
+        assert_eq!(result.return_data_as::<u32>().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_build_error_has_no_metadata() {
+        let err = TransactionError::BuildError("No signers provided".to_string());
+        assert!(err.logs().is_empty());
+        assert_eq!(err.compute_units(), 0);
+        assert_eq!(err.instruction_index(), None);
+        assert!(err.transaction_error().is_none());
+    }
+}
@@ -0,0 +1,199 @@
+//! Programs owned by the upgradeable BPF loader
+//!
+//! `LiteSVM::add_program` deploys with the non-upgradeable loader, so there is
+//! no `ProgramData` account and programs that check their own upgrade
+//! authority cannot run. This module writes the `Program` and `ProgramData`
+//! accounts the way `solana program deploy` leaves them, and rewrites them to
+//! upgrade a program in place.
+
+use litesvm::LiteSVM;
+use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_program::clock::Clock;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_sdk::account::Account;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum UpgradeError {
+    #[error("Program {0} is not deployed with the upgradeable loader")]
+    NotUpgradeable(Pubkey),
+
+    #[error("Program {0} is immutable")]
+    Immutable(Pubkey),
+
+    #[error("Upgrade authority mismatch for program {program_id}: expected {expected}, got {actual}")]
+    AuthorityMismatch {
+        /// The program being upgraded
+        program_id: Pubkey,
+        /// The authority stored in `ProgramData`
+        expected: Pubkey,
+        /// The authority that was passed
+        actual: Pubkey,
+    },
+
+    #[error("Failed to deploy program {0}: {1}")]
+    Deploy(Pubkey, String),
+}
+
+/// The `ProgramData` address of an upgradeable program
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    bpf_loader_upgradeable::get_program_data_address(program_id)
+}
+
+/// Read the upgrade authority of an upgradeable program
+///
+/// Returns `Ok(None)` for immutable programs.
+pub(crate) fn upgrade_authority(svm: &LiteSVM, program_id: &Pubkey) -> Result<Option<Pubkey>, UpgradeError> {
+    let not_upgradeable = || UpgradeError::NotUpgradeable(*program_id);
+
+    let program = svm
+        .get_account(program_id)
+        .filter(|account| account.owner == bpf_loader_upgradeable::id())
+        .ok_or_else(not_upgradeable)?;
+    let Ok(UpgradeableLoaderState::Program { programdata_address }) = bincode::deserialize(&program.data) else {
+        return Err(not_upgradeable());
+    };
+
+    let programdata = svm.get_account(&programdata_address).ok_or_else(not_upgradeable)?;
+    let metadata = programdata
+        .data
+        .get(..UpgradeableLoaderState::size_of_programdata_metadata())
+        .ok_or_else(not_upgradeable)?;
+    match bincode::deserialize(metadata) {
+        Ok(UpgradeableLoaderState::ProgramData { upgrade_authority_address, .. }) => {
+            Ok(upgrade_authority_address)
+        }
+        _ => Err(not_upgradeable()),
+    }
+}
+
+/// Write the `ProgramData` and `Program` accounts and load the program
+///
+/// `ProgramData` is written first, since LiteSVM compiles the program when the
+/// executable `Program` account is set.
+pub(crate) fn deploy(
+    svm: &mut LiteSVM,
+    program_id: Pubkey,
+    program_bytes: &[u8],
+    upgrade_authority: Option<Pubkey>,
+) -> Result<(), UpgradeError> {
+    let slot = svm.get_sysvar::<Clock>().slot;
+    let rent = svm.get_sysvar::<Rent>();
+    let programdata_address = program_data_address(&program_id);
+
+    let mut programdata = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot,
+        upgrade_authority_address: upgrade_authority,
+    })
+    .expect("ProgramData state serializes");
+    programdata.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+    programdata.extend_from_slice(program_bytes);
+
+    let program = bincode::serialize(&UpgradeableLoaderState::Program { programdata_address })
+        .expect("Program state serializes");
+
+    let accounts = [
+        (programdata_address, programdata, false),
+        (program_id, program, true),
+    ];
+    for (address, data, executable) in accounts {
+        let account = Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable,
+            rent_epoch: 0,
+        };
+        svm.set_account(address, account)
+            .map_err(|e| UpgradeError::Deploy(program_id, format!("{:?}", e)))?;
+    }
+    Ok(())
+}
+
+/// Replace the program's code, checking the upgrade authority like the loader does
+pub(crate) fn upgrade(
+    svm: &mut LiteSVM,
+    program_id: Pubkey,
+    program_bytes: &[u8],
+    authority: &Pubkey,
+) -> Result<(), UpgradeError> {
+    match upgrade_authority(svm, &program_id)? {
+        None => Err(UpgradeError::Immutable(program_id)),
+        Some(expected) if expected != *authority => Err(UpgradeError::AuthorityMismatch {
+            program_id,
+            expected,
+            actual: *authority,
+        }),
+        Some(expected) => deploy(svm, program_id, program_bytes, Some(expected)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_programdata_layout() {
+        let authority = Pubkey::new_unique();
+        let data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 7,
+            upgrade_authority_address: Some(authority),
+        })
+        .unwrap();
+        assert_eq!(data.len(), UpgradeableLoaderState::size_of_programdata_metadata());
+
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            program_data_address(&program_id),
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+        );
+    }
+
+    #[test]
+    fn test_upgrade_replaces_program_data() {
+        let mut svm = LiteSVM::new();
+        let token = svm.get_account(&spl_token::id()).unwrap().data;
+        let token_2022 = svm.get_account(&spl_token_2022::id()).unwrap().data;
+        assert_ne!(token, token_2022);
+
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        deploy(&mut svm, program_id, &token, Some(authority)).unwrap();
+        let before = svm.get_account(&program_data_address(&program_id)).unwrap();
+
+        svm.warp_to_slot(100);
+        upgrade(&mut svm, program_id, &token_2022, &authority).unwrap();
+
+        let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+        let after = svm.get_account(&program_data_address(&program_id)).unwrap();
+        assert_eq!(&after.data[metadata_len..], token_2022.as_slice());
+        assert_eq!(
+            bincode::deserialize::<UpgradeableLoaderState>(&after.data[..metadata_len]).unwrap(),
+            UpgradeableLoaderState::ProgramData {
+                slot: 100,
+                upgrade_authority_address: Some(authority),
+            }
+        );
+        assert_eq!(after.lamports, svm.minimum_balance_for_rent_exemption(after.data.len()));
+        assert_ne!(after.lamports, before.lamports);
+        assert_eq!(upgrade_authority(&svm, &program_id).unwrap(), Some(authority));
+        assert!(svm.get_account(&program_id).unwrap().executable);
+    }
+
+    #[test]
+    fn test_upgrade_requires_upgradeable_program() {
+        let mut svm = LiteSVM::new();
+        let program_id = Pubkey::new_unique();
+        assert!(matches!(
+            upgrade(&mut svm, program_id, &[], &Pubkey::new_unique()),
+            Err(UpgradeError::NotUpgradeable(id)) if id == program_id
+        ));
+
+        // Loader-v2 programs added by `add_program` have no ProgramData
+        assert!(matches!(
+            upgrade_authority(&svm, &spl_token::id()),
+            Err(UpgradeError::NotUpgradeable(_))
+        ));
+    }
+}
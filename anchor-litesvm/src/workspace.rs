@@ -0,0 +1,271 @@
+//! Anchor workspace discovery
+//!
+//! Reads the `[programs.localnet]` table of `Anchor.toml` and locates the
+//! artifacts `anchor build` writes for each program:
+//! - `target/deploy/<name>.so`
+//! - `target/idl/<name>.json` (optional)
+//!
+//! so tests no longer need to spell out program IDs and `include_bytes!` paths.
+
+use crate::idl::{self, Idl, IdlError};
+use solana_program::pubkey::Pubkey;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// File name of the Anchor workspace manifest
+pub const ANCHOR_TOML: &str = "Anchor.toml";
+
+#[derive(Error, Debug)]
+pub enum WorkspaceError {
+    #[error("No Anchor.toml found in {0} or any parent directory")]
+    NotFound(PathBuf),
+
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        /// The file that could not be read
+        path: PathBuf,
+        /// The underlying IO error
+        source: std::io::Error,
+    },
+
+    #[error("Invalid {path}: {reason}")]
+    InvalidManifest {
+        /// The `Anchor.toml` file
+        path: PathBuf,
+        /// What is wrong with it
+        reason: String,
+    },
+
+    #[error("Program `{name}` has not been built: {path} does not exist. Run `anchor build` first.")]
+    ProgramNotBuilt {
+        /// The program name from `Anchor.toml`, or the file name
+        name: String,
+        /// The expected `.so` file
+        path: PathBuf,
+    },
+
+    #[error("Failed to load IDL {path}: {source}")]
+    Idl {
+        /// The IDL file
+        path: PathBuf,
+        /// The underlying IDL error
+        source: IdlError,
+    },
+}
+
+/// A program declared in `Anchor.toml`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceProgram {
+    /// The program name (the `[programs.localnet]` key)
+    pub name: String,
+    /// The declared program ID
+    pub program_id: Pubkey,
+    /// Where `anchor build` writes the program binary
+    pub so_path: PathBuf,
+    /// Where `anchor build` writes the IDL
+    pub idl_path: PathBuf,
+}
+
+impl WorkspaceProgram {
+    /// Read the compiled program
+    pub fn read_program(&self) -> Result<Vec<u8>, WorkspaceError> {
+        read_program_file(&self.name, &self.so_path)
+    }
+
+    /// Load the IDL, or `None` if it was not generated
+    pub fn load_idl(&self) -> Result<Option<Idl>, WorkspaceError> {
+        if !self.idl_path.exists() {
+            return Ok(None);
+        }
+        idl::load_idl(&self.idl_path)
+            .map(Some)
+            .map_err(|source| WorkspaceError::Idl {
+                path: self.idl_path.clone(),
+                source,
+            })
+    }
+}
+
+/// An Anchor workspace: the directory holding `Anchor.toml` and its programs
+///
+/// # Example
+///
+/// ```ignore
+/// let workspace = AnchorWorkspace::discover()?;
+/// for program in workspace.programs() {
+///     println!("{} {}", program.name, program.program_id);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AnchorWorkspace {
+    root: PathBuf,
+    programs: Vec<WorkspaceProgram>,
+}
+
+impl AnchorWorkspace {
+    /// Find `Anchor.toml` in the test crate's directory or one of its parents
+    ///
+    /// Starts from `CARGO_MANIFEST_DIR`, or the working directory when not run by cargo.
+    pub fn discover() -> Result<Self, WorkspaceError> {
+        let start = match std::env::var_os("CARGO_MANIFEST_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::current_dir().map_err(|source| WorkspaceError::Io {
+                path: PathBuf::from("."),
+                source,
+            })?,
+        };
+        Self::discover_from(start)
+    }
+
+    /// Find `Anchor.toml` in `dir` or one of its parents
+    pub fn discover_from<P: AsRef<Path>>(dir: P) -> Result<Self, WorkspaceError> {
+        let dir = dir.as_ref();
+        dir.ancestors()
+            .find(|ancestor| ancestor.join(ANCHOR_TOML).is_file())
+            .ok_or_else(|| WorkspaceError::NotFound(dir.to_path_buf()))
+            .and_then(Self::load)
+    }
+
+    /// Load the workspace whose `Anchor.toml` is in `root`
+    ///
+    /// Programs are listed in name order. Entries may be a program ID string
+    /// or a table with an `address` key.
+    pub fn load<P: AsRef<Path>>(root: P) -> Result<Self, WorkspaceError> {
+        let root = root.as_ref().to_path_buf();
+        let manifest = root.join(ANCHOR_TOML);
+        let invalid = |reason: String| WorkspaceError::InvalidManifest {
+            path: manifest.clone(),
+            reason,
+        };
+
+        let text = std::fs::read_to_string(&manifest).map_err(|source| WorkspaceError::Io {
+            path: manifest.clone(),
+            source,
+        })?;
+        let table: toml::Table = toml::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+
+        let localnet = table
+            .get("programs")
+            .and_then(|programs| programs.get("localnet"))
+            .and_then(toml::Value::as_table)
+            .ok_or_else(|| invalid("missing [programs.localnet] table".to_string()))?;
+
+        let mut programs = Vec::new();
+        for (name, entry) in localnet {
+            let address = entry
+                .as_str()
+                .or_else(|| entry.get("address").and_then(toml::Value::as_str))
+                .ok_or_else(|| invalid(format!("program `{}` has no address", name)))?;
+            let program_id = Pubkey::from_str(address)
+                .map_err(|_| invalid(format!("program `{}` has invalid address `{}`", name, address)))?;
+            programs.push(WorkspaceProgram {
+                name: name.clone(),
+                program_id,
+                so_path: root.join("target/deploy").join(format!("{}.so", name)),
+                idl_path: root.join("target/idl").join(format!("{}.json", name)),
+            });
+        }
+        programs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        if programs.is_empty() {
+            return Err(invalid("[programs.localnet] declares no programs".to_string()));
+        }
+        Ok(Self { root, programs })
+    }
+
+    /// The directory containing `Anchor.toml`
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The declared programs, in name order
+    pub fn programs(&self) -> &[WorkspaceProgram] {
+        &self.programs
+    }
+
+    /// Find a declared program by name
+    pub fn program(&self, name: &str) -> Option<&WorkspaceProgram> {
+        self.programs.iter().find(|program| program.name == name)
+    }
+}
+
+/// Read a compiled program, reporting a missing file as not built
+pub(crate) fn read_program_file(name: &str, path: &Path) -> Result<Vec<u8>, WorkspaceError> {
+    std::fs::read(path).map_err(|source| match source.kind() {
+        std::io::ErrorKind::NotFound => WorkspaceError::ProgramNotBuilt {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+        },
+        _ => WorkspaceError::Io {
+            path: path.to_path_buf(),
+            source,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_workspace(name: &str, anchor_toml: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("anchor_litesvm_ws_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("programs/escrow/src")).unwrap();
+        std::fs::write(root.join(ANCHOR_TOML), anchor_toml).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_discover_programs() {
+        let root = temp_workspace(
+            "discover",
+            r#"
+                [programs.localnet]
+                escrow = "8LTee82TkoqBoBjBAz2yAAKSj9ckr7zz5vMi6rJQTwhJ"
+                amm = { address = "11111111111111111111111111111112", idl = "idl/amm.json" }
+
+                [programs.devnet]
+                escrow = "11111111111111111111111111111113"
+            "#,
+        );
+
+        let workspace = AnchorWorkspace::discover_from(root.join("programs/escrow/src")).unwrap();
+        assert_eq!(workspace.root(), root.as_path());
+        let names: Vec<_> = workspace.programs().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["amm", "escrow"]);
+
+        let escrow = workspace.program("escrow").unwrap();
+        assert_eq!(
+            escrow.program_id,
+            Pubkey::from_str("8LTee82TkoqBoBjBAz2yAAKSj9ckr7zz5vMi6rJQTwhJ").unwrap()
+        );
+        assert_eq!(escrow.so_path, root.join("target/deploy/escrow.so"));
+
+        // Not built yet
+        let err = escrow.read_program().unwrap_err();
+        assert!(matches!(err, WorkspaceError::ProgramNotBuilt { ref name, .. } if name == "escrow"));
+        assert!(err.to_string().contains("anchor build"));
+        assert!(escrow.load_idl().unwrap().is_none());
+
+        std::fs::create_dir_all(root.join("target/deploy")).unwrap();
+        std::fs::write(&escrow.so_path, [1, 2, 3]).unwrap();
+        assert_eq!(escrow.read_program().unwrap(), vec![1, 2, 3]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_manifest() {
+        let root = temp_workspace("invalid", "[programs.localnet]\nescrow = \"not-a-key\"\n");
+        let err = AnchorWorkspace::load(&root).unwrap_err();
+        assert!(err.to_string().contains("invalid address `not-a-key`"));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let missing = std::env::temp_dir().join("anchor_litesvm_ws_missing");
+        assert!(matches!(
+            AnchorWorkspace::discover_from(&missing),
+            Err(WorkspaceError::NotFound(_))
+        ));
+    }
+}
@@ -1,12 +1,11 @@
+use anchor_client::Program;
+use anchor_litesvm::{AnchorContext, LiteSvmClient};
 use litesvm::LiteSVM;
 use solana_sdk::{
-    commitment_config::CommitmentConfig,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     transaction::Transaction,
 };
-use anchor_client::{Client, Cluster, Program};
-use solana_client::rpc_client::RpcClient;
 use std::rc::Rc;
 use spl_associated_token_account::get_associated_token_address;
 use solana_program_pack::Pack;
@@ -123,18 +122,13 @@ fn test_make_with_anchor_client() {
 
     let vault = get_associated_token_address(&escrow_pda, &mint_a.pubkey());
 
-    let _mock_rpc = RpcClient::new_mock("succeeds");
+    // Hand the prepared LiteSVM to anchor-client; the maker pays for requests
+    let client = LiteSvmClient::new(AnchorContext::new(svm, program_id));
+    let program: Program<Rc<Keypair>> = client
+        .program(Rc::new(maker.insecure_clone()), program_id)
+        .unwrap();
 
-    let payer_keypair = Keypair::new(); 
-    let client = Client::new_with_options(
-        Cluster::Custom("http://127.0.0.1:8899".to_string(), "ws://127.0.0.1:8900".to_string()),
-        Rc::new(payer_keypair),
-        CommitmentConfig::confirmed(),
-    );
-
-    let program: Program<Rc<Keypair>> = client.program(program_id).unwrap();
-
-    let make_ix = program
+    let result = program
         .request()
         .accounts(anchor_escrow::client::accounts::Make {
             maker: maker.pubkey(),
@@ -152,28 +146,14 @@ fn test_make_with_anchor_client() {
             receive: 500_000_000,  // 0.5 tokens
             amount: 1_000_000_000,  // 1 token
         })
-        .instructions()
-        .unwrap()
-        .remove(0);
-
-    // Build and send transaction using LiteSVM
-    let tx = Transaction::new_signed_with_payer(
-        &[make_ix],
-        Some(&maker.pubkey()),
-        &[&maker],
-        svm.latest_blockhash(),
-    );
-
-    // Execute and verify
-    let result = svm.send_transaction(tx);
+        .send();
 
+    // Verify
     match result {
-        Ok(res) => {
-            println!("Transaction succeeded with anchor_client!");
-            println!("\nTransaction logs:");
-            for log in &res.logs {
-                println!("  {}", log);
-            }
+        Ok(signature) => {
+            println!("Transaction succeeded with anchor_client: {}", signature);
+            let ctx = client.context();
+            let svm = &ctx.svm;
 
             // Verify escrow account was created
             let escrow_account = svm.get_account(&escrow_pda);
@@ -335,19 +315,17 @@ fn test_take_with_anchor_client() {
 
     let vault = get_associated_token_address(&escrow_pda, &mint_a.pubkey());
 
-    let _mock_rpc = RpcClient::new_mock("succeeds");
-
-    let payer_keypair = Keypair::new();
-    let client = Client::new_with_options(
-        Cluster::Custom("http://127.0.0.1:8899".to_string(), "ws://127.0.0.1:8900".to_string()),
-        Rc::new(payer_keypair),
-        CommitmentConfig::confirmed(),
-    );
-
-    let program: Program<Rc<Keypair>> = client.program(program_id).unwrap();
+    // Hand the prepared LiteSVM to anchor-client, one program per payer
+    let client = LiteSvmClient::new(AnchorContext::new(svm, program_id));
+    let maker_program: Program<Rc<Keypair>> = client
+        .program(Rc::new(maker.insecure_clone()), program_id)
+        .unwrap();
+    let taker_program: Program<Rc<Keypair>> = client
+        .program(Rc::new(taker.insecure_clone()), program_id)
+        .unwrap();
 
     // Create the escrow first
-    let make_ix = program
+    maker_program
         .request()
         .accounts(anchor_escrow::client::accounts::Make {
             maker: maker.pubkey(),
@@ -365,18 +343,8 @@ fn test_take_with_anchor_client() {
             receive: 500_000_000,  // 0.5 tokens
             amount: 1_000_000_000,  // 1 token
         })
-        .instructions()
-        .unwrap()
-        .remove(0);
-
-    // Send make transaction
-    let tx = Transaction::new_signed_with_payer(
-        &[make_ix],
-        Some(&maker.pubkey()),
-        &[&maker],
-        svm.latest_blockhash(),
-    );
-    svm.send_transaction(tx).unwrap();
+        .send()
+        .unwrap();
 
     println!("Escrow created successfully");
 
@@ -384,7 +352,7 @@ fn test_take_with_anchor_client() {
     let taker_ata_a = get_associated_token_address(&taker.pubkey(), &mint_a.pubkey());
     let maker_ata_b = get_associated_token_address(&maker.pubkey(), &mint_b.pubkey());
 
-    let take_ix = program
+    let result = taker_program
         .request()
         .accounts(anchor_escrow::client::accounts::Take {
             taker: taker.pubkey(),
@@ -401,28 +369,14 @@ fn test_take_with_anchor_client() {
             system_program: solana_sdk::system_program::id(),
         })
         .args(anchor_escrow::client::args::Take {})
-        .instructions()
-        .unwrap()
-        .remove(0);
-
-    // Build and send take transaction using LiteSVM
-    let tx = Transaction::new_signed_with_payer(
-        &[take_ix],
-        Some(&taker.pubkey()),
-        &[&taker],
-        svm.latest_blockhash(),
-    );
-
-    // Execute and verify
-    let result = svm.send_transaction(tx);
+        .send();
 
+    // Verify
     match result {
-        Ok(res) => {
-            println!("\nTake transaction succeeded with anchor_client!");
-            println!("\nTransaction logs:");
-            for log in &res.logs {
-                println!("  {}", log);
-            }
+        Ok(signature) => {
+            println!("\nTake transaction succeeded with anchor_client: {}", signature);
+            let ctx = client.context();
+            let svm = &ctx.svm;
 
             // Verify escrow account was closed
             // In LiteSVM, closed accounts might still exist with 0 lamports and 0 data
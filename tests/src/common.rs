@@ -0,0 +1,174 @@
+//! Escrow setup shared by the integration tests
+
+use anchor_litesvm::{AnchorContext, AnchorLiteSVM, InstructionBuilder, TestHelpers, tuple_args};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::signer::keypair::keypair_from_seed;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+pub const PROGRAM_ID: Pubkey = Pubkey::from_str_const("8LTee82TkoqBoBjBAz2yAAKSj9ckr7zz5vMi6rJQTwhJ");
+pub const PROGRAM_BYTES: &[u8] = include_bytes!("../../target/deploy/anchor_escrow.so");
+
+pub const SEED: u64 = 42;
+/// Mint B the maker asks for
+pub const RECEIVE: u64 = 500_000_000;
+/// Mint A the maker deposits into the vault
+pub const DEPOSIT: u64 = 1_000_000_000;
+
+/// Context with only the escrow program deployed
+pub fn escrow_context() -> AnchorContext {
+    AnchorLiteSVM::build_with_program(PROGRAM_ID, PROGRAM_BYTES)
+}
+
+/// Funded maker and taker, both mints and the accounts of a not yet made offer
+pub struct Escrow {
+    pub maker: Keypair,
+    pub taker: Keypair,
+    pub mint_a: Keypair,
+    pub mint_b: Keypair,
+    /// SPL Token or Token-2022, whichever owns the mints
+    pub token_program: Pubkey,
+    pub maker_ata_a: Pubkey,
+    pub taker_ata_b: Pubkey,
+    pub escrow: Pubkey,
+    pub vault: Pubkey,
+}
+
+impl Escrow {
+    /// Set up an offer over two SPL Token mints, whose taker holds exactly the requested amount
+    pub fn new(ctx: &mut AnchorContext) -> Self {
+        Self::with_taker_tokens(ctx, RECEIVE)
+    }
+
+    /// Like [`Self::new`], but the taker holds `taker_tokens` of mint B
+    pub fn with_taker_tokens(ctx: &mut AnchorContext, taker_tokens: u64) -> Self {
+        let maker = ctx.create_funded_account(10_000_000_000).unwrap();
+        let taker = ctx.create_funded_account(10_000_000_000).unwrap();
+        let mint_a = ctx.create_token_mint(&maker, 9).unwrap();
+        let mint_b = ctx.create_token_mint(&maker, 9).unwrap();
+        Self::setup(ctx, maker, taker, mint_a, mint_b, taker_tokens)
+    }
+
+    /// Like [`Self::new`], but every keypair is derived from `id`
+    ///
+    /// PDA and ATA bumps then come out the same on every run, and so does the
+    /// compute unit usage. Use a different `id` for each offer in one context.
+    pub fn fixed(ctx: &mut AnchorContext, id: u8) -> Self {
+        let [maker, taker, mint_a, mint_b] =
+            [0u8, 1, 2, 3].map(|role| keypair_from_seed(&[[id, role].as_slice(), &[0; 30]].concat()).unwrap());
+        ctx.batch_airdrop(&[&maker.pubkey(), &taker.pubkey()], 10_000_000_000).unwrap();
+        create_mint(ctx, &maker, &mint_a);
+        create_mint(ctx, &maker, &mint_b);
+        Self::setup(ctx, maker, taker, mint_a, mint_b, RECEIVE)
+    }
+
+    /// Set up an offer over mints created by the test, such as Token-2022 mints with extensions
+    ///
+    /// `maker` must be the mint authority of both mints.
+    pub fn with_mints(ctx: &mut AnchorContext, maker: Keypair, mint_a: Keypair, mint_b: Keypair) -> Self {
+        let taker = ctx.create_funded_account(10_000_000_000).unwrap();
+        Self::setup(ctx, maker, taker, mint_a, mint_b, RECEIVE)
+    }
+
+    fn setup(
+        ctx: &mut AnchorContext,
+        maker: Keypair,
+        taker: Keypair,
+        mint_a: Keypair,
+        mint_b: Keypair,
+        taker_tokens: u64,
+    ) -> Self {
+        let token_program = ctx.svm.get_account(&mint_a.pubkey()).unwrap().owner;
+        let maker_ata_a = ctx.create_token_account(&maker, &mint_a.pubkey(), Some((DEPOSIT, &maker))).unwrap();
+        let taker_ata_b = ctx.create_token_account(&taker, &mint_b.pubkey(), Some((taker_tokens, &maker))).unwrap();
+
+        let (escrow, _) = ctx.find_pda(&[b"escrow", maker.pubkey().as_ref(), &SEED.to_le_bytes()]);
+        let vault = get_associated_token_address_with_program_id(&escrow, &mint_a.pubkey(), &token_program);
+
+        Self { maker, taker, mint_a, mint_b, token_program, maker_ata_a, taker_ata_b, escrow, vault }
+    }
+
+    pub fn taker_ata_a(&self) -> Pubkey {
+        get_associated_token_address_with_program_id(&self.taker.pubkey(), &self.mint_a.pubkey(), &self.token_program)
+    }
+
+    pub fn maker_ata_b(&self) -> Pubkey {
+        get_associated_token_address_with_program_id(&self.maker.pubkey(), &self.mint_b.pubkey(), &self.token_program)
+    }
+
+    /// The maker's `make` instruction, signed by `self.maker`
+    pub fn make(&self, ctx: &AnchorContext) -> InstructionBuilder {
+        ctx.instruction_builder("make")
+            .signer("maker", &self.maker)
+            .account_mut("escrow", self.escrow)
+            .account("mint_a", self.mint_a.pubkey())
+            .account("mint_b", self.mint_b.pubkey())
+            .account_mut("maker_ata_a", self.maker_ata_a)
+            .account_mut("vault", self.vault)
+            .associated_token_program()
+            .account("token_program", self.token_program)
+            .system_program()
+            .args(tuple_args((SEED, RECEIVE, DEPOSIT)))
+    }
+
+    /// The taker's `take` instruction, signed by `self.taker`
+    pub fn take(&self, ctx: &AnchorContext) -> InstructionBuilder {
+        ctx.instruction_builder("take")
+            .signer("taker", &self.taker)
+            .account_mut("maker", self.maker.pubkey())
+            .account_mut("escrow", self.escrow)
+            .account("mint_a", self.mint_a.pubkey())
+            .account("mint_b", self.mint_b.pubkey())
+            .account_mut("vault", self.vault)
+            .account_mut("taker_ata_a", self.taker_ata_a())
+            .account_mut("taker_ata_b", self.taker_ata_b)
+            .account_mut("maker_ata_b", self.maker_ata_b())
+            .associated_token_program()
+            .account("token_program", self.token_program)
+            .system_program()
+            .args(tuple_args(()))
+    }
+
+    /// The maker's `refund` instruction, signed by `self.maker`
+    pub fn refund(&self, ctx: &AnchorContext) -> InstructionBuilder {
+        ctx.instruction_builder("refund")
+            .signer("maker", &self.maker)
+            .account_mut("escrow", self.escrow)
+            .account("mint_a", self.mint_a.pubkey())
+            .account_mut("vault", self.vault)
+            .account_mut("maker_ata_a", self.maker_ata_a)
+            .associated_token_program()
+            .account("token_program", self.token_program)
+            .system_program()
+            .args(tuple_args(()))
+    }
+
+    /// Execute `make` and assert that it succeeded
+    pub fn made(ctx: &mut AnchorContext) -> Self {
+        let escrow = Self::new(ctx);
+        escrow.make(ctx).execute(ctx, &[&escrow.maker]).unwrap().assert_success();
+        escrow
+    }
+}
+
+/// Create a 9-decimal SPL Token mint at the address of `mint`
+fn create_mint(ctx: &mut AnchorContext, authority: &Keypair, mint: &Keypair) {
+    let space = spl_token::state::Mint::LEN;
+    let rent = ctx.svm.minimum_balance_for_rent_exemption(space);
+    let instructions = [
+        system_instruction::create_account(&authority.pubkey(), &mint.pubkey(), rent, space as u64, &spl_token::id()),
+        spl_token::instruction::initialize_mint2(&spl_token::id(), &mint.pubkey(), &authority.pubkey(), None, 9)
+            .unwrap(),
+    ];
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&authority.pubkey()),
+        &[authority, mint],
+        ctx.svm.latest_blockhash(),
+    );
+    ctx.svm.send_transaction(tx).unwrap();
+    ctx.track_accounts([mint.pubkey()]);
+}
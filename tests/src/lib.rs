@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod common;
+
 #[cfg(test)]
 mod test_make;
 
@@ -32,4 +35,54 @@ mod regular_litesvm_test;
 mod anchor_litesvm_test;
 
 #[cfg(test)]
-mod anchor_client_with_litesvm_test;
\ No newline at end of file
+mod anchor_client_with_litesvm_test;
+#[cfg(test)]
+mod test_idl_resolution;
+
+#[cfg(test)]
+mod test_error_assertions;
+
+#[cfg(test)]
+mod test_snapshot_rollback;
+
+#[cfg(test)]
+mod test_token_2022;
+
+#[cfg(test)]
+mod test_transfer_fee;
+
+#[cfg(test)]
+mod test_balance_changes;
+
+#[cfg(test)]
+mod test_discriminators;
+
+#[cfg(test)]
+mod test_program_ix;
+
+#[cfg(test)]
+mod test_simulation;
+
+#[cfg(test)]
+mod test_call_tree;
+
+#[cfg(test)]
+mod test_cu_baseline;
+
+#[cfg(test)]
+mod test_account_snapshot;
+
+#[cfg(test)]
+mod test_fixtures;
+
+#[cfg(test)]
+mod test_workspace;
+
+#[cfg(test)]
+mod test_upgradeable;
+
+#[cfg(test)]
+mod test_clones;
+
+#[cfg(test)]
+mod test_runtime_options;
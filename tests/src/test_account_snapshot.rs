@@ -0,0 +1,31 @@
+use crate::common::{escrow_context, Escrow};
+use anchor_litesvm::{AccountSnapshot, AssertionHelpers};
+use solana_sdk::signature::Signer;
+
+/// Escrow and vault state checked against `tests/snapshots/*.snap`
+///
+/// Run with `UPDATE_ACCOUNT_SNAPSHOTS=1` after intentional state changes.
+#[test]
+fn test_escrow_account_snapshots() {
+    let mut ctx = escrow_context();
+    ctx.load_idl(concat!(env!("CARGO_MANIFEST_DIR"), "/../target/idl/anchor_escrow.json"))
+        .unwrap();
+    let escrow = Escrow::made(&mut ctx);
+
+    // Keys from Keypair::new() and the PDA bump change on every run
+    let snapshot = |name: &str| {
+        AccountSnapshot::new(name)
+            .redact(&escrow.maker.pubkey(), "maker")
+            .redact(&escrow.mint_a.pubkey(), "mint_a")
+            .redact(&escrow.mint_b.pubkey(), "mint_b")
+            .redact(&escrow.escrow, "escrow")
+            .redact_field("bump")
+    };
+    snapshot("escrow_after_make").assert(&ctx, &escrow.escrow);
+    snapshot("vault_after_make").assert(&ctx, &escrow.vault);
+
+    escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap().assert_success();
+
+    // Closed accounts have nothing run-specific left to redact
+    ctx.assert_account_snapshot("escrow_after_take", &escrow.escrow);
+}
@@ -0,0 +1,46 @@
+use crate::common::{escrow_context, Escrow, DEPOSIT, RECEIVE};
+use solana_sdk::signature::Signer;
+
+/// Make and take, checking every balance delta with expect_changes
+#[test]
+fn test_escrow_balance_changes() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::new(&mut ctx);
+
+    // MAKE: tokens move from the maker into the vault
+    let mut changes = ctx.expect_changes(&[
+        ("maker_ata_a", escrow.maker_ata_a),
+        ("vault", escrow.vault),
+    ]);
+
+    escrow.make(&ctx).execute(&mut ctx, &[&escrow.maker]).unwrap().assert_success();
+
+    changes
+        .tokens("maker_ata_a", -(DEPOSIT as i128))
+        .tokens("vault", DEPOSIT as i128)
+        .verify(&ctx);
+
+    // TAKE: the taker pays fees, so the maker gains exactly the closed accounts' rent
+    let mut changes = ctx.expect_changes(&[
+        ("maker", escrow.maker.pubkey()),
+        ("escrow", escrow.escrow),
+        ("vault", escrow.vault),
+        ("taker_ata_a", escrow.taker_ata_a()),
+        ("taker_ata_b", escrow.taker_ata_b),
+        ("maker_ata_b", escrow.maker_ata_b()),
+    ]);
+
+    escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap().assert_success();
+
+    let escrow_rent = changes.lamports_before("escrow") as i128;
+    let vault_rent = changes.lamports_before("vault") as i128;
+    changes
+        .lamports("maker", vault_rent + escrow_rent)
+        .lamports("escrow", -escrow_rent)
+        .lamports("vault", -vault_rent)
+        .tokens("vault", -(DEPOSIT as i128))
+        .tokens("taker_ata_a", DEPOSIT as i128)
+        .tokens("taker_ata_b", -(RECEIVE as i128))
+        .tokens("maker_ata_b", RECEIVE as i128)
+        .verify(&ctx);
+}
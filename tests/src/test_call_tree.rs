@@ -0,0 +1,52 @@
+use crate::common::{escrow_context, Escrow};
+use anchor_litesvm::CallResult;
+
+/// `take` creates two ATAs, then makes two transfers and closes the vault
+#[test]
+fn test_take_call_tree() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::made(&mut ctx);
+
+    let result = escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap();
+    let tree = result.call_tree();
+    println!("{}", tree);
+
+    assert_eq!(tree.roots.len(), 1);
+    let root = &tree.roots[0];
+    assert_eq!(root.instruction_name.as_deref(), Some("take"));
+    assert_eq!(root.result, CallResult::Success);
+    assert_eq!(root.compute_units, Some(result.compute_units()));
+
+    let token_calls: Vec<_> = root
+        .children
+        .iter()
+        .filter(|call| call.program_id == spl_token::id())
+        .map(|call| call.instruction_name.as_deref().unwrap())
+        .collect();
+    assert_eq!(token_calls, vec!["transfer_checked", "transfer_checked", "close_account"]);
+    assert_eq!(tree.calls_to(&spl_associated_token_account::id()).count(), 2);
+
+    // Every CPI carries its instruction data
+    assert!(tree.iter().skip(1).all(|call| call.data.is_some()));
+    assert!(tree.failed().is_none());
+    assert!(tree.most_expensive().is_some());
+}
+
+/// A taker without enough tokens fails in the first `transfer_checked` CPI
+#[test]
+fn test_failed_take_call_tree() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::with_taker_tokens(&mut ctx, 100);
+    escrow.make(&ctx).execute(&mut ctx, &[&escrow.maker]).unwrap().assert_success();
+
+    let err = escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap_err();
+    let tree = err.call_tree();
+
+    let failed = tree.failed().unwrap();
+    assert_eq!(failed.program_id, spl_token::id());
+    assert_eq!(failed.depth, 2);
+    assert_eq!(failed.instruction_name.as_deref(), Some("transfer_checked"));
+    assert!(failed.logs.iter().any(|log| log.contains("insufficient funds")));
+    assert!(failed.data.is_some());
+    assert!(tree.roots[0].is_failed());
+}
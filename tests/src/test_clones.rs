@@ -0,0 +1,47 @@
+use crate::common::{escrow_context, PROGRAM_BYTES, PROGRAM_ID};
+use anchor_litesvm::{AnchorLiteSVM, AssertionHelpers, TestHelpers};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+/// A clone cache built from one session stands in for mainnet dumps in another
+#[test]
+fn test_clone_cache() {
+    let mut source = escrow_context();
+    let authority = source.create_funded_account(10_000_000_000).unwrap();
+    let mint = source.create_token_mint(&authority, 6).unwrap();
+
+    // programs/<ID>.so and accounts/<PUBKEY>.json, as `solana program dump` and
+    // `solana account --output json` would produce them
+    let cache = std::env::temp_dir().join(format!("escrow_clones_{}", std::process::id()));
+    let cloned_program = Pubkey::new_unique();
+    std::fs::create_dir_all(cache.join("programs")).unwrap();
+    std::fs::write(cache.join("programs").join(format!("{}.so", cloned_program)), PROGRAM_BYTES).unwrap();
+    source.dump_accounts_at(cache.join("accounts"), &[mint.pubkey(), authority.pubkey()]).unwrap();
+
+    let mut ctx = AnchorLiteSVM::new()
+        .deploy_program(PROGRAM_ID, PROGRAM_BYTES)
+        .with_clone_cache(&cache)
+        .unwrap()
+        .build();
+    assert_eq!(ctx.program_id, PROGRAM_ID);
+    assert!(ctx.svm.get_account(&cloned_program).unwrap().executable);
+    ctx.assert_account_owner(&mint.pubkey(), &spl_token::id());
+
+    // The cloned mint works with its original authority
+    let holder = ctx.create_funded_account(1_000_000_000).unwrap();
+    let ata = ctx.create_token_account(&holder, &mint.pubkey(), Some((5_000_000, &authority))).unwrap();
+    ctx.assert_token_balance(&ata, 5_000_000);
+
+    // Single files work the same way
+    let mint_file = cache.join("accounts").join(format!("{}.json", mint.pubkey()));
+    let ctx = AnchorLiteSVM::new()
+        .deploy_program(PROGRAM_ID, PROGRAM_BYTES)
+        .with_cloned_program(cloned_program, cache.join("programs").join(format!("{}.so", cloned_program)))
+        .unwrap()
+        .with_cloned_account(mint.pubkey(), &mint_file)
+        .unwrap()
+        .build();
+    ctx.assert_account_owner(&mint.pubkey(), &spl_token::id());
+
+    std::fs::remove_dir_all(&cache).unwrap();
+}
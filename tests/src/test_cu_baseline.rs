@@ -0,0 +1,56 @@
+use crate::common::{escrow_context, Escrow};
+use anchor_litesvm::{CuBaseline, CuBaselineError};
+
+/// Make, take and refund, checked against `tests/cu_baseline.json`
+///
+/// Record it with `UPDATE_CU_BASELINE=1` after `anchor build` and after intentional
+/// program changes.
+#[test]
+fn test_escrow_cu_baseline() {
+    // Fixed keypairs keep PDA and ATA bumps, and so usage, identical between runs
+    let baseline = CuBaseline::default();
+
+    let mut ctx = escrow_context();
+    let taken = Escrow::fixed(&mut ctx, 0);
+    let make = taken.make(&ctx).execute(&mut ctx, &[&taken.maker]).unwrap();
+    make.record_cu_with(&baseline, "escrow::make");
+    taken
+        .take(&ctx)
+        .execute(&mut ctx, &[&taken.taker])
+        .unwrap()
+        .record_cu_with(&baseline, "escrow::take");
+
+    let refunded = Escrow::fixed(&mut ctx, 1);
+    refunded.make(&ctx).execute(&mut ctx, &[&refunded.maker]).unwrap().assert_success();
+    refunded
+        .refund(&ctx)
+        .execute(&mut ctx, &[&refunded.maker])
+        .unwrap()
+        .record_cu_with(&baseline, "escrow::refund");
+}
+
+/// A baseline recorded lower than the current usage is a regression
+#[test]
+fn test_escrow_cu_regression() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::new(&mut ctx);
+    let make = escrow.make(&ctx).execute(&mut ctx, &[&escrow.maker]).unwrap();
+
+    let path = std::env::temp_dir().join(format!("escrow_cu_{}.json", std::process::id()));
+    std::fs::write(&path, format!("{{ \"escrow::make\": {} }}", make.compute_units() - 1_000)).unwrap();
+
+    // Checked regardless of UPDATE_CU_BASELINE in the environment
+    let strict = CuBaseline::new(&path).update(false);
+    assert!(matches!(
+        strict.check("escrow::make", make.compute_units()),
+        Err(CuBaselineError::Regression { .. })
+    ));
+    assert!(matches!(
+        strict.check("escrow::take", 1),
+        Err(CuBaselineError::Missing { .. })
+    ));
+    let lenient = strict.tolerance_percent(50.0);
+    lenient.check("escrow::make", make.compute_units()).unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,32 @@
+use crate::common::{escrow_context, Escrow, RECEIVE, SEED};
+use anchor_escrow::state::Escrow as EscrowState;
+use anchor_litesvm::AccountError;
+use solana_sdk::signature::Signer;
+
+/// `Escrow` uses `#[account(discriminator = 1)]`
+#[test]
+fn test_escrow_custom_account_discriminator() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::made(&mut ctx);
+
+    // The one-byte discriminator is checked, then the rest is deserialized
+    let state: EscrowState = ctx.get_anchor_account(&escrow.escrow).unwrap();
+    assert_eq!(state.seed, SEED);
+    assert_eq!(state.maker, escrow.maker.pubkey());
+    assert_eq!(state.receive, RECEIVE);
+
+    // Skipping the discriminator unchecked still skips only its one byte
+    let unchecked: EscrowState = ctx.get_anchor_account_skip_discriminator(&escrow.escrow).unwrap();
+    assert_eq!(unchecked.seed, SEED);
+    assert_eq!(unchecked.maker, escrow.maker.pubkey());
+
+    // A system account has no data, so its discriminator can't match
+    match ctx.get_anchor_account::<EscrowState>(&escrow.maker.pubkey()) {
+        Err(AccountError::DiscriminatorMismatch { expected, actual }) => {
+            assert_eq!(expected, vec![1]);
+            assert!(actual.is_empty());
+        }
+        Err(other) => panic!("expected DiscriminatorMismatch, got {}", other),
+        Ok(_) => panic!("expected DiscriminatorMismatch, got an Escrow"),
+    }
+}
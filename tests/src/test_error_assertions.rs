@@ -0,0 +1,36 @@
+use crate::common::{escrow_context, Escrow, DEPOSIT, SEED};
+use anchor_escrow::errors::EscrowError;
+use anchor_lang::error::ErrorCode;
+use anchor_litesvm::{AnchorErrorAssertions, TestHelpers, tuple_args};
+
+#[test]
+fn test_make_with_zero_amount_fails_with_invalid_amount() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::new(&mut ctx);
+
+    let result = escrow
+        .make(&ctx)
+        .args(tuple_args((SEED, 0u64, DEPOSIT)))
+        .execute(&mut ctx, &[&escrow.maker]);
+
+    result.assert_anchor_error(EscrowError::InvalidAmount);
+    result.assert_anchor_error_code(6000);
+
+    let info = result.anchor_error().unwrap();
+    assert_eq!(info.name.as_deref(), Some("InvalidAmount"));
+    assert_eq!(info.message.as_deref(), Some("Invalid amount"));
+}
+
+#[test]
+fn test_take_with_wrong_maker_fails_with_constraint_seeds() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::made(&mut ctx);
+
+    // The escrow seeds are derived from `maker`, so passing someone else breaks them
+    let impostor = ctx.create_funded_account(1_000_000_000).unwrap();
+    let forged = Escrow { maker: impostor, ..escrow };
+    let result = forged.take(&ctx).execute(&mut ctx, &[&forged.taker]);
+
+    result.assert_constraint_error(ErrorCode::ConstraintSeeds);
+    assert_eq!(result.anchor_error().unwrap().origin.as_deref(), Some("escrow"));
+}
@@ -0,0 +1,33 @@
+use crate::common::{escrow_context, Escrow, DEPOSIT, PROGRAM_BYTES, PROGRAM_ID};
+use anchor_litesvm::{AnchorLiteSVM, AssertionHelpers};
+
+/// State saved after `make` is reloaded into a fresh context and taken there
+#[test]
+fn test_dump_and_reload_escrow_state() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::made(&mut ctx);
+
+    let dir = std::env::temp_dir().join(format!("escrow_fixtures_{}", std::process::id()));
+    // Everything the context has seen, without listing the accounts
+    let saved = ctx.dump_accounts(&dir).unwrap();
+    assert!(saved.contains(&escrow.escrow));
+    assert!(saved.contains(&escrow.taker_ata_b));
+    assert!(!saved.contains(&PROGRAM_ID));
+    assert!(dir.join(format!("{}.json", escrow.escrow)).exists());
+
+    // A fresh environment only knows the program and the saved accounts
+    let mut ctx = AnchorLiteSVM::new()
+        .load_accounts_from_dir(&dir)
+        .unwrap()
+        .deploy_program(PROGRAM_ID, PROGRAM_BYTES)
+        .build();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    ctx.assert_token_balance(&escrow.vault, DEPOSIT);
+    ctx.assert_account_owner(&escrow.escrow, &PROGRAM_ID);
+
+    escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap().assert_success();
+
+    ctx.assert_accounts_closed(&[&escrow.escrow, &escrow.vault]);
+    ctx.assert_token_balance(&escrow.taker_ata_a(), DEPOSIT);
+}
@@ -0,0 +1,45 @@
+use crate::common::{escrow_context, Escrow, DEPOSIT, RECEIVE, SEED};
+use anchor_litesvm::{AssertionHelpers, tuple_args};
+use solana_sdk::signature::Signer;
+
+/// Escrow flow where the IDL resolves every derivable account
+#[test]
+fn test_escrow_with_idl_resolution() {
+    let mut ctx = escrow_context();
+    ctx.load_idl(concat!(env!("CARGO_MANIFEST_DIR"), "/../target/idl/anchor_escrow.json"))
+        .unwrap();
+    let escrow = Escrow::new(&mut ctx);
+
+    // MAKE: escrow, maker_ata_a, vault and both programs come from the IDL
+    ctx.instruction_builder("make")
+        .signer("maker", &escrow.maker)
+        .account("mint_a", escrow.mint_a.pubkey())
+        .account("mint_b", escrow.mint_b.pubkey())
+        .token_program()
+        .args(tuple_args((SEED, RECEIVE, DEPOSIT)))
+        .execute(&mut ctx, &[&escrow.maker])
+        .unwrap()
+        .assert_success();
+
+    ctx.assert_account_exists(&escrow.escrow);
+    ctx.assert_token_balance(&escrow.vault, DEPOSIT);
+    ctx.assert_token_balance(&escrow.maker_ata_a, 0);
+
+    // TAKE: the escrow seeds read `escrow.seed`, so the escrow is passed explicitly
+    ctx.instruction_builder("take")
+        .signer("taker", &escrow.taker)
+        .account_mut("maker", escrow.maker.pubkey())
+        .account_mut("escrow", escrow.escrow)
+        .account("mint_a", escrow.mint_a.pubkey())
+        .account("mint_b", escrow.mint_b.pubkey())
+        .token_program()
+        .args(tuple_args(()))
+        .execute(&mut ctx, &[&escrow.taker])
+        .unwrap()
+        .assert_success();
+
+    ctx.assert_accounts_closed(&[&escrow.escrow, &escrow.vault]);
+    ctx.assert_token_balance(&escrow.taker_ata_a(), DEPOSIT);
+    ctx.assert_token_balance(&escrow.taker_ata_b, 0);
+    ctx.assert_token_balance(&escrow.maker_ata_b(), RECEIVE);
+}
@@ -0,0 +1,67 @@
+use crate::common::{escrow_context, Escrow, DEPOSIT, RECEIVE, SEED};
+use anchor_litesvm::AssertionHelpers;
+use solana_sdk::signature::Signer;
+
+// Generate client modules from IDL. `self::` tells the generated module
+// apart from the `anchor_escrow` crate this test crate also depends on.
+anchor_lang::declare_program!(anchor_escrow);
+use self::anchor_escrow::client::{accounts, args};
+
+/// Make and take using the typed client structs, no instruction names
+#[test]
+fn test_escrow_with_program_ix() {
+    let mut ctx = escrow_context();
+    assert_eq!(ctx.program_id, self::anchor_escrow::ID);
+    let escrow = Escrow::new(&mut ctx);
+
+    let result = ctx
+        .program_ix(
+            accounts::Make {
+                maker: escrow.maker.pubkey(),
+                escrow: escrow.escrow,
+                mint_a: escrow.mint_a.pubkey(),
+                mint_b: escrow.mint_b.pubkey(),
+                maker_ata_a: escrow.maker_ata_a,
+                vault: escrow.vault,
+                associated_token_program: spl_associated_token_account::id(),
+                token_program: spl_token::id(),
+                system_program: solana_sdk::system_program::id(),
+            },
+            args::Make {
+                seed: SEED,
+                receive: RECEIVE,
+                amount: DEPOSIT,
+            },
+        )
+        .execute(&mut ctx, &[&escrow.maker])
+        .unwrap();
+    result.assert_success();
+    assert!(result.has_log("Instruction: Make"));
+
+    ctx.assert_token_balance(&escrow.vault, DEPOSIT);
+
+    ctx.program_ix(
+        accounts::Take {
+            taker: escrow.taker.pubkey(),
+            maker: escrow.maker.pubkey(),
+            escrow: escrow.escrow,
+            mint_a: escrow.mint_a.pubkey(),
+            mint_b: escrow.mint_b.pubkey(),
+            vault: escrow.vault,
+            taker_ata_a: escrow.taker_ata_a(),
+            taker_ata_b: escrow.taker_ata_b,
+            maker_ata_b: escrow.maker_ata_b(),
+            associated_token_program: spl_associated_token_account::id(),
+            token_program: spl_token::id(),
+            system_program: solana_sdk::system_program::id(),
+        },
+        args::Take {},
+    )
+    .execute(&mut ctx, &[&escrow.taker])
+    .unwrap()
+    .assert_success();
+
+    ctx.assert_accounts_closed(&[&escrow.escrow, &escrow.vault]);
+    ctx.assert_token_balance(&escrow.taker_ata_a(), DEPOSIT);
+    ctx.assert_token_balance(&escrow.maker_ata_b(), RECEIVE);
+}
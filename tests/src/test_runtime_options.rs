@@ -0,0 +1,47 @@
+use crate::common::{Escrow, PROGRAM_BYTES, PROGRAM_ID};
+use anchor_litesvm::{AnchorContext, AnchorLiteSVM};
+
+/// Run `make` and return whether it succeeded and the compute units it used
+fn make(ctx: &mut AnchorContext) -> (bool, u64) {
+    let escrow = Escrow::new(ctx);
+    match escrow.make(ctx).execute(ctx, &[&escrow.maker]) {
+        Ok(result) => (true, result.compute_units()),
+        Err(err) => (false, err.compute_units()),
+    }
+}
+
+/// A compute unit cap well below what `make` needs makes it fail
+///
+/// Usage varies slightly between runs because the PDA bump depends on random keys.
+#[test]
+fn test_compute_unit_limit() {
+    let mut ctx = AnchorLiteSVM::new()
+        .deploy_program(PROGRAM_ID, PROGRAM_BYTES)
+        .with_sigverify(false)
+        .build();
+    let (success, used) = make(&mut ctx);
+    assert!(success);
+
+    let mut ctx = AnchorLiteSVM::new()
+        .deploy_program(PROGRAM_ID, PROGRAM_BYTES)
+        .with_compute_unit_limit(used / 2)
+        .build();
+    let (success, used_capped) = make(&mut ctx);
+    assert!(!success);
+    assert!(used_capped <= used / 2);
+}
+
+/// Programs still deploy and run when the feature set is built with a gate turned off
+#[test]
+fn test_without_feature() {
+    let feature_id = solana_feature_set::enable_loader_v4::id();
+    let mut ctx = AnchorLiteSVM::new()
+        .deploy_program(PROGRAM_ID, PROGRAM_BYTES)
+        .without_feature(feature_id)
+        .build();
+    // The loader-v4 builtin is only added when its feature is active
+    assert!(ctx.svm.get_account(&solana_sdk::loader_v4::id()).is_none());
+
+    let (success, _) = make(&mut ctx);
+    assert!(success);
+}
@@ -0,0 +1,36 @@
+use crate::common::{escrow_context, Escrow, DEPOSIT, RECEIVE};
+use anchor_litesvm::AssertionHelpers;
+use anchor_litesvm::test_helpers::token::get_token_balance;
+
+/// Probe `take` with a simulation, then execute it for real
+#[test]
+fn test_simulate_take_before_executing() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::made(&mut ctx);
+    let taker_ata_a = escrow.taker_ata_a();
+    let take = escrow.take(&ctx);
+
+    // SIMULATE: the result shows what take would do...
+    let simulated = take.clone().simulate(&ctx, &[&escrow.taker]).unwrap();
+    assert!(simulated.compute_units() > 0);
+    assert!(simulated.has_log("Instruction: Take"));
+    assert_eq!(simulated.post_account(&escrow.vault).unwrap().lamports, 0);
+    assert_eq!(simulated.post_account(&escrow.escrow).unwrap().lamports, 0);
+    assert!(simulated.post_account(&taker_ata_a).unwrap().lamports > 0);
+
+    // ...without changing anything
+    ctx.assert_account_exists(&escrow.escrow);
+    ctx.assert_account_exists(&escrow.vault);
+    assert_eq!(get_token_balance(&ctx, &escrow.vault).unwrap(), DEPOSIT);
+    assert_eq!(get_token_balance(&ctx, &escrow.taker_ata_b).unwrap(), RECEIVE);
+    assert!(ctx.svm.get_account(&taker_ata_a).is_none());
+
+    // EXECUTE: the same instruction still succeeds afterwards
+    let executed = take.execute(&mut ctx, &[&escrow.taker]).unwrap();
+    executed.assert_success();
+    assert_eq!(executed.compute_units(), simulated.compute_units());
+
+    ctx.assert_accounts_closed(&[&escrow.escrow, &escrow.vault]);
+    assert_eq!(get_token_balance(&ctx, &taker_ata_a).unwrap(), DEPOSIT);
+    assert_eq!(get_token_balance(&ctx, &escrow.maker_ata_b()).unwrap(), RECEIVE);
+}
@@ -0,0 +1,28 @@
+use crate::common::{escrow_context, Escrow, DEPOSIT, RECEIVE};
+use anchor_litesvm::AssertionHelpers;
+
+/// One make, then both take and refund branches from the same snapshot
+#[test]
+fn test_take_and_refund_from_one_setup() {
+    let mut ctx = escrow_context();
+    let escrow = Escrow::made(&mut ctx);
+
+    let after_make = ctx.snapshot();
+
+    // Branch 1: taker accepts the offer
+    escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap().assert_success();
+
+    ctx.assert_accounts_closed(&[&escrow.escrow, &escrow.vault]);
+    ctx.assert_token_balance(&escrow.taker_ata_b, 0);
+
+    // Branch 2: rewind and let the maker refund instead
+    ctx.restore(&after_make);
+    ctx.assert_account_exists(&escrow.escrow);
+    ctx.assert_token_balance(&escrow.vault, DEPOSIT);
+    ctx.assert_token_balance(&escrow.taker_ata_b, RECEIVE);
+
+    escrow.refund(&ctx).execute(&mut ctx, &[&escrow.maker]).unwrap().assert_success();
+
+    ctx.assert_accounts_closed(&[&escrow.escrow, &escrow.vault]);
+    ctx.assert_token_balance(&escrow.maker_ata_a, DEPOSIT);
+}
@@ -0,0 +1,36 @@
+use crate::common::{escrow_context, Escrow, DEPOSIT, RECEIVE};
+use anchor_litesvm::test_helpers::token::get_mint;
+use anchor_litesvm::{AssertionHelpers, TestHelpers};
+use solana_sdk::signature::Signer;
+
+/// Full make and take flow with Token-2022 mints
+#[test]
+fn test_escrow_with_token_2022() {
+    let mut ctx = escrow_context();
+    let token_program = spl_token_2022::id();
+
+    let maker = ctx.create_funded_account(10_000_000_000).unwrap();
+    let mint_a = ctx.create_token_mint_with_program(&maker, 6, &token_program).unwrap();
+    let mint_b = ctx.create_token_mint_with_program(&maker, 6, &token_program).unwrap();
+
+    ctx.assert_account_owner(&mint_a.pubkey(), &token_program);
+    assert_eq!(get_mint(&ctx, &mint_a.pubkey()).unwrap().decimals, 6);
+
+    // Token program is picked up from the mint owner
+    let escrow = Escrow::with_mints(&mut ctx, maker, mint_a, mint_b);
+    assert_eq!(escrow.token_program, token_program);
+    ctx.assert_account_owner(&escrow.maker_ata_a, &token_program);
+    ctx.assert_token_balance(&escrow.maker_ata_a, DEPOSIT);
+
+    escrow.make(&ctx).execute(&mut ctx, &[&escrow.maker]).unwrap().assert_success();
+
+    ctx.assert_token_balance(&escrow.vault, DEPOSIT);
+    ctx.assert_token_balance(&escrow.maker_ata_a, 0);
+
+    escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap().assert_success();
+
+    ctx.assert_accounts_closed(&[&escrow.escrow, &escrow.vault]);
+    ctx.assert_token_balance(&escrow.taker_ata_a(), DEPOSIT);
+    ctx.assert_token_balance(&escrow.taker_ata_b, 0);
+    ctx.assert_token_balance(&escrow.maker_ata_b(), RECEIVE);
+}
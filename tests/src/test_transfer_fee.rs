@@ -0,0 +1,69 @@
+use crate::common::{escrow_context, Escrow, DEPOSIT, RECEIVE};
+use anchor_litesvm::{AssertionHelpers, TestHelpers};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError as SdkTransactionError;
+use spl_token_2022::error::TokenError;
+
+const FEE_BASIS_POINTS: u16 = 100; // 1%
+/// High enough that the rate, not the cap, decides the fee
+const MAX_FEE: u64 = 100_000_000;
+
+/// Fee on the receive mint: the maker gets `receive` minus the transfer fee
+#[test]
+fn test_take_with_fee_on_mint_b() {
+    let mut ctx = escrow_context();
+    let maker = ctx.create_funded_account(10_000_000_000).unwrap();
+    let mint_a = ctx.mint_builder().decimals(6).create(&mut ctx, &maker).unwrap();
+    let mint_b = ctx.mint_builder()
+        .decimals(6)
+        .transfer_fee(FEE_BASIS_POINTS, MAX_FEE)
+        .create(&mut ctx, &maker)
+        .unwrap();
+    let escrow = Escrow::with_mints(&mut ctx, maker, mint_a, mint_b);
+
+    escrow.make(&ctx).execute(&mut ctx, &[&escrow.maker]).unwrap().assert_success();
+    escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap().assert_success();
+
+    // Taker pays the full amount, the fee is withheld from what the maker receives
+    let fee = RECEIVE * FEE_BASIS_POINTS as u64 / 10_000;
+    ctx.assert_token_balance(&escrow.taker_ata_b, 0);
+    ctx.assert_token_balance(&escrow.maker_ata_b(), RECEIVE - fee);
+    ctx.assert_token_balance(&escrow.taker_ata_a(), DEPOSIT);
+    ctx.assert_accounts_closed(&[&escrow.escrow, &escrow.vault]);
+}
+
+/// Fee on the deposit mint: the vault is short by the fee and keeps withheld
+/// fees after the payout, so Token-2022 refuses to close it and take fails
+#[test]
+fn test_take_with_fee_on_mint_a() {
+    let mut ctx = escrow_context();
+    let maker = ctx.create_funded_account(10_000_000_000).unwrap();
+    let mint_a = ctx.mint_builder()
+        .decimals(6)
+        .transfer_fee(FEE_BASIS_POINTS, MAX_FEE)
+        .create(&mut ctx, &maker)
+        .unwrap();
+    let mint_b = ctx.mint_builder().decimals(6).create(&mut ctx, &maker).unwrap();
+    let escrow = Escrow::with_mints(&mut ctx, maker, mint_a, mint_b);
+
+    escrow.make(&ctx).execute(&mut ctx, &[&escrow.maker]).unwrap().assert_success();
+
+    let deposit_fee = DEPOSIT * FEE_BASIS_POINTS as u64 / 10_000;
+    ctx.assert_token_balance(&escrow.maker_ata_a, 0);
+    ctx.assert_token_balance(&escrow.vault, DEPOSIT - deposit_fee);
+
+    let err = escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap_err();
+
+    assert_eq!(
+        err.transaction_error(),
+        Some(&SdkTransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenError::AccountHasWithheldTransferFees as u32),
+        ))
+    );
+
+    // Nothing moved: the escrow and its vault are untouched
+    ctx.assert_account_exists(&escrow.escrow);
+    ctx.assert_token_balance(&escrow.vault, DEPOSIT - deposit_fee);
+    ctx.assert_token_balance(&escrow.taker_ata_b, RECEIVE);
+}
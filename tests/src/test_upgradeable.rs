@@ -0,0 +1,40 @@
+use crate::common::{Escrow, PROGRAM_BYTES, PROGRAM_ID};
+use anchor_litesvm::{program_data_address, AnchorLiteSVM, AssertionHelpers, UpgradeError};
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Escrow made with one deployment is taken after an upgrade
+#[test]
+fn test_upgradeable_escrow() {
+    let authority = Keypair::new();
+    let mut ctx = AnchorLiteSVM::new()
+        .deploy_upgradeable_program(PROGRAM_ID, PROGRAM_BYTES, authority.pubkey())
+        .build();
+
+    ctx.assert_account_owner(&PROGRAM_ID, &bpf_loader_upgradeable::id());
+    ctx.assert_account_owner(&program_data_address(&PROGRAM_ID), &bpf_loader_upgradeable::id());
+    assert_eq!(ctx.program_upgrade_authority(&PROGRAM_ID).unwrap(), Some(authority.pubkey()));
+
+    let escrow = Escrow::made(&mut ctx);
+
+    // Only the upgrade authority may replace the program
+    let err = ctx.upgrade_program(PROGRAM_ID, PROGRAM_BYTES, &Keypair::new()).unwrap_err();
+    assert!(matches!(err, UpgradeError::AuthorityMismatch { .. }));
+
+    // Other code under the same ID no longer understands the escrow's instructions
+    let token_bytes = ctx.svm.get_account(&spl_token::id()).unwrap().data;
+    ctx.upgrade_program(PROGRAM_ID, &token_bytes, &authority).unwrap();
+    let program_data = ctx.svm.get_account(&program_data_address(&PROGRAM_ID)).unwrap();
+    assert!(program_data.data.ends_with(&token_bytes));
+    assert!(escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).is_err());
+
+    ctx.upgrade_program(PROGRAM_ID, PROGRAM_BYTES, &authority).unwrap();
+    assert_eq!(ctx.program_upgrade_authority(&PROGRAM_ID).unwrap(), Some(authority.pubkey()));
+
+    // The failed take is in the transaction history, so sign the retry with a new blockhash
+    ctx.svm.expire_blockhash();
+
+    escrow.take(&ctx).execute(&mut ctx, &[&escrow.taker]).unwrap().assert_success();
+
+    ctx.assert_accounts_closed(&[&escrow.escrow, &escrow.vault]);
+}
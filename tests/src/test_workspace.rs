@@ -0,0 +1,40 @@
+use anchor_litesvm::{
+    AnchorLiteSVM, AnchorWorkspace, AssertionHelpers, TestHelpers, tuple_args,
+};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+/// Programs, IDLs and IDs come from `Anchor.toml` and `target/`
+#[test]
+fn test_escrow_from_anchor_workspace() {
+    let workspace = AnchorWorkspace::discover().unwrap();
+    let escrow = workspace.program("anchor_escrow").unwrap();
+    assert_eq!(
+        escrow.program_id,
+        Pubkey::from_str_const("8LTee82TkoqBoBjBAz2yAAKSj9ckr7zz5vMi6rJQTwhJ")
+    );
+
+    let mut ctx = AnchorLiteSVM::from_anchor_workspace().unwrap();
+    assert_eq!(ctx.program_id, escrow.program_id);
+    assert!(ctx.idl().is_some());
+
+    let maker = ctx.create_funded_account(10_000_000_000).unwrap();
+    let mint_a = ctx.create_token_mint(&maker, 9).unwrap();
+    let mint_b = ctx.create_token_mint(&maker, 9).unwrap();
+    ctx.create_token_account(&maker, &mint_a.pubkey(), Some((1_000_000_000, &maker))).unwrap();
+
+    // The loaded IDL resolves escrow, vault and the program accounts
+    let seed = 42u64;
+    ctx.instruction_builder("make")
+        .signer("maker", &maker)
+        .account("mint_a", mint_a.pubkey())
+        .account("mint_b", mint_b.pubkey())
+        .token_program()
+        .args(tuple_args((seed, 500_000_000u64, 1_000_000_000u64)))
+        .execute(&mut ctx, &[&maker])
+        .unwrap()
+        .assert_success();
+
+    let (escrow_pda, _) = ctx.find_pda(&[b"escrow", maker.pubkey().as_ref(), &seed.to_le_bytes()]);
+    ctx.assert_account_owner(&escrow_pda, &escrow.program_id);
+}